@@ -0,0 +1,188 @@
+use crate::{
+    ff::Field,
+    protocol::prss::{FromRandomU128, PrssIndex, SharedRandomness},
+    secret_sharing::replicated::{
+        semi_honest::AdditiveShare as Replicated, ReplicatedSecretSharing,
+    },
+};
+
+/// Identifies a single Beaver triple within a PRSS index space.
+///
+/// Drawing a triple's `a` and `b` operands needs two independent `F_rand` calls, and masking
+/// `c`'s cross terms needs a third, so each triple claims three consecutive slots of the index
+/// space rather than one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TripleIndex(u128);
+
+impl TripleIndex {
+    fn slot(self, offset: u128) -> PrssIndex {
+        PrssIndex::from(self.0 * 3 + offset)
+    }
+}
+
+/// This helper's half of a Beaver multiplication triple's product term `c = a * b`.
+///
+/// `a` and `b` are complete, usable replicated shares as soon as they are drawn from PRSS, but
+/// `c`'s cross terms (`a_i * b_{i+1} + a_{i+1} * b_i`) are only known to this helper as a
+/// non-replicated additive share, and must be resolved into a proper [`Replicated`] sharing by
+/// one round of communication: this helper sends [`Self::to_send`] to its right neighbor, and
+/// combines the value it receives from its left neighbor with its own local term to complete a
+/// [`Replicated<F>`] for `c`.
+#[derive(Debug, Clone, Copy)]
+pub struct BeaverC<F: Field> {
+    local: F,
+}
+
+impl<F: Field> BeaverC<F> {
+    /// The value this helper sends to its right neighbor to resolve `c`.
+    #[must_use]
+    pub fn to_send(&self) -> F {
+        self.local
+    }
+
+    /// Combine this helper's local term with the value received from its left neighbor to
+    /// produce the completed replicated sharing of `c`.
+    #[must_use]
+    pub fn complete(self, received_from_left: F) -> Replicated<F> {
+        Replicated::new(received_from_left, self.local)
+    }
+}
+
+/// Draw the next Beaver multiplication triple `(a, b, c)` with `c = a * b`, for use in
+/// offline/online multiplication: a protocol can pre-generate a batch of these, then turn each
+/// online multiplication into purely local operations on its shares plus the one reshare that
+/// [`BeaverC::complete`] finishes.
+///
+/// `a` and `b` are sampled locally via two `F_rand` calls (the PRSS-based replicated random
+/// sharing already implemented in [`SharedRandomness::generate`]). `c`'s cross terms are computed
+/// locally too, then masked with a fresh PRSS zero-sharing so that the value this helper will
+/// send to complete the reshare reveals nothing about `a`, `b`, or `c`.
+#[must_use]
+pub fn next_beaver_triple<F, I>(
+    prss: &(impl SharedRandomness + ?Sized),
+    index: I,
+) -> (Replicated<F>, Replicated<F>, BeaverC<F>)
+where
+    F: Field + FromRandomU128,
+    I: Into<PrssIndex>,
+{
+    let index = TripleIndex(index.into().as_u128());
+    let a: Replicated<F> = prss.generate(index.slot(0));
+    let b: Replicated<F> = prss.generate(index.slot(1));
+    let mask: F = prss.zero(index.slot(2));
+
+    let local_c = a.left() * b.left() + a.left() * b.right() + a.right() * b.left() + mask;
+
+    (a, b, BeaverC { local: local_c })
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::{ArrayLength, GenericArray};
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+
+    use super::next_beaver_triple;
+    use crate::{
+        ff::Fp31,
+        protocol::prss::{Generator, GeneratorFactory, PrssIndex, SharedRandomness},
+        secret_sharing::replicated::{
+            semi_honest::AdditiveShare as Replicated, ReplicatedSecretSharing,
+        },
+    };
+
+    /// A `SharedRandomness` for one of three simulated helpers in a ring, built from two
+    /// independent [`Generator`]s: `left` is the key shared with the helper to the left (the same
+    /// key that helper uses as its own `right`), and `right` is the key shared with the helper to
+    /// the right (likewise that helper's own `left`).
+    struct SimulatedHelper {
+        left: Generator,
+        right: Generator,
+    }
+
+    impl SharedRandomness for SimulatedHelper {
+        fn generate_arrays<I: Into<PrssIndex>, N: ArrayLength>(
+            &self,
+            index: I,
+        ) -> (GenericArray<u128, N>, GenericArray<u128, N>) {
+            let base = index.into().as_u128() * u128::try_from(N::to_usize()).unwrap();
+            let mut l = GenericArray::<u128, N>::default();
+            let mut r = GenericArray::<u128, N>::default();
+            for i in 0..N::to_usize() {
+                let offset = base + u128::try_from(i).unwrap();
+                l[i] = self.left.generate(offset);
+                r[i] = self.right.generate(offset);
+            }
+            (l, r)
+        }
+    }
+
+    /// Three helpers sharing one [`Generator`] per adjacent pair, so that each helper's "right"
+    /// half is always drawn from the same key as its right neighbor's "left" half, matching a
+    /// real three-party PRSS ring.
+    fn simulated_ring() -> [SimulatedHelper; 3] {
+        let generator = |seed: u8| {
+            GeneratorFactory {
+                kdf: Hkdf::<Sha256>::new(None, &[seed; 32]),
+            }
+            .generator(b"beaver_tests")
+        };
+        [
+            SimulatedHelper {
+                left: generator(3),
+                right: generator(1),
+            },
+            SimulatedHelper {
+                left: generator(1),
+                right: generator(2),
+            },
+            SimulatedHelper {
+                left: generator(2),
+                right: generator(3),
+            },
+        ]
+    }
+
+    /// Reconstructs the secret a valid three-helper replicated sharing represents: the sum of
+    /// every helper's "left" half.
+    fn reconstruct(shares: &[Replicated<Fp31>; 3]) -> Fp31 {
+        shares[0].left() + shares[1].left() + shares[2].left()
+    }
+
+    #[test]
+    fn completed_triple_satisfies_c_equals_a_times_b() {
+        let ring = simulated_ring();
+        let index = PrssIndex::from(11_u32);
+
+        let triples: [_; 3] =
+            std::array::from_fn(|i| next_beaver_triple::<Fp31, _>(&ring[i], index));
+        let a: [Replicated<Fp31>; 3] = std::array::from_fn(|i| triples[i].0.clone());
+        let b: [Replicated<Fp31>; 3] = std::array::from_fn(|i| triples[i].1.clone());
+
+        // Helper `i` sends `to_send()` to its right neighbor `i + 1` and completes `c` with the
+        // value received from its left neighbor, `i + 2` (mod 3).
+        let completed_c: [Replicated<Fp31>; 3] = std::array::from_fn(|i| {
+            let received_from_left = triples[(i + 2) % 3].2.to_send();
+            triples[i].2.complete(received_from_left)
+        });
+
+        assert_eq!(
+            reconstruct(&a) * reconstruct(&b),
+            reconstruct(&completed_c),
+            "the completed triple must satisfy c = a * b"
+        );
+
+        // `reconstruct` only sums `.left()` across the ring, which is invariant under swapping
+        // `completed_c[i]`'s `.left()`/`.right()` halves -- it can't tell a structurally valid
+        // replicated sharing from one whose halves are transposed. Check the ring invariant
+        // directly: helper `i`'s left half must equal helper `i - 1`'s right half, for every `i`.
+        for i in 0..3 {
+            assert_eq!(
+                completed_c[(i + 2) % 3].right(),
+                completed_c[i].left(),
+                "completed_c[{}].right() must equal completed_c[{i}].left()",
+                (i + 2) % 3,
+            );
+        }
+    }
+}
@@ -0,0 +1,34 @@
+pub mod beaver;
+mod crypto;
+
+pub use crypto::{
+    limbs_to_be_bytes, reduce_mod_be, refresh, EpochId, FromPrss, FromRandom, FromRandomU128,
+    Generator, GeneratorFactory, KeyExchange, SharedRandomness,
+};
+
+/// Identifies a single invocation of the PRSS generator.
+///
+/// Anything that uniquely identifies a step within a protocol run (a record ID, a gate index, or
+/// a small integer disambiguating multiple draws within the same step) can be turned into a
+/// `PrssIndex` and used to draw pseudo-random values via [`SharedRandomness`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrssIndex(u128);
+
+impl PrssIndex {
+    #[must_use]
+    pub fn as_u128(self) -> u128 {
+        self.0
+    }
+}
+
+impl From<u128> for PrssIndex {
+    fn from(value: u128) -> Self {
+        Self(value)
+    }
+}
+
+impl From<u32> for PrssIndex {
+    fn from(value: u32) -> Self {
+        Self(u128::from(value))
+    }
+}
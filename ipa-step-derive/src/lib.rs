@@ -0,0 +1,126 @@
+//! `#[derive(CompactStep)]`: generates an [`ipa_step::CompactStep`] implementation for an enum
+//! whose variants are annotated with `#[step(...)]`, so protocol step hierarchies don't need to
+//! hand-write `index`/`step_string`/`step_narrow_type`/`step_index_from_str`. The heavy lifting
+//! for a single variant lives in [`variant::VariantAttribute`]; this crate root just walks the
+//! enum's variants and assembles their accumulated token streams into the trait's methods.
+
+mod sum;
+mod variant;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{spanned::Spanned, Data, DeriveInput};
+
+use crate::{sum::ExtendedSum, variant::VariantAttribute};
+
+/// Convenience for turning anything with a span (a [`Spanned`] syntax node, or a bare
+/// [`proc_macro2::Span`] already extracted via `.span()`) directly into a `syn::Error`, wrapped
+/// in a `Result` to match the `?`-friendly signatures `VariantAttribute` uses throughout.
+pub(crate) trait IntoSpan {
+    fn error<T>(&self, message: impl std::fmt::Display) -> Result<T, syn::Error>;
+}
+
+impl<S: Spanned> IntoSpan for S {
+    fn error<T>(&self, message: impl std::fmt::Display) -> Result<T, syn::Error> {
+        Err(syn::Error::new(self.span(), message.to_string()))
+    }
+}
+
+impl IntoSpan for Span {
+    fn error<T>(&self, message: impl std::fmt::Display) -> Result<T, syn::Error> {
+        Err(syn::Error::new(*self, message.to_string()))
+    }
+}
+
+#[proc_macro_derive(CompactStep, attributes(step))]
+pub fn derive_compact_step(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match derive_compact_step_impl(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn derive_compact_step_impl(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
+    let Data::Enum(data) = &input.data else {
+        return input
+            .ident
+            .span()
+            .error("#[derive(CompactStep)] only applies to enums");
+    };
+
+    let ident = &input.ident;
+    let variants = VariantAttribute::parse_attrs(data)?;
+
+    let mut arm_count = ExtendedSum::new();
+    let mut index_arms = TokenStream::new();
+    let mut name_arrays = TokenStream::new();
+    let mut as_ref_arms = TokenStream::new();
+    let mut step_string_arms = TokenStream::new();
+    let mut step_narrow_arms = TokenStream::new();
+    let mut step_index_from_str_arms = TokenStream::new();
+
+    for variant in &variants {
+        arm_count = variant.generate(
+            &arm_count,
+            &mut index_arms,
+            &mut name_arrays,
+            &mut as_ref_arms,
+            &mut step_string_arms,
+            &mut step_narrow_arms,
+            &mut step_index_from_str_arms,
+        );
+    }
+
+    Ok(quote! {
+        #name_arrays
+
+        impl ::ipa_step::Step for #ident {}
+
+        impl ::std::convert::AsRef<str> for #ident {
+            fn as_ref(&self) -> &str {
+                match self {
+                    #as_ref_arms
+                }
+            }
+        }
+
+        impl ::ipa_step::CompactStep for #ident {
+            const STEP_COUNT: usize = #arm_count;
+
+            fn index(&self) -> usize {
+                match self {
+                    #index_arms
+                }
+            }
+
+            fn step_string(i: usize) -> String {
+                match i {
+                    #step_string_arms
+                    _ => panic!("step index {i} out of range for {}", stringify!(#ident)),
+                }
+            }
+
+            fn step_narrow_type(i: usize) -> Option<&'static str> {
+                match i {
+                    #step_narrow_arms
+                    _ => None,
+                }
+            }
+
+            /// Assembled from each variant's `step_index_from_str_arms` contribution: split
+            /// `s` into its first path segment and the (optional) remainder, then try each
+            /// variant's own name (and, for variants with a child, recurse into it) in turn.
+            fn step_index_from_str(s: &str) -> Option<::ipa_step::CompactGateIndex> {
+                let (head, tail) = match s.split_once('/') {
+                    Some((head, tail)) => (head, Some(tail)),
+                    None => (s, None),
+                };
+
+                #step_index_from_str_arms
+
+                None
+            }
+        }
+    })
+}
@@ -0,0 +1,47 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{quote, ToTokens};
+
+/// An additive expression combining a compile-time-known `usize` count with zero or more
+/// runtime terms (typically another step's `STEP_COUNT`), so that the running step-count total
+/// threaded through [`crate::variant::VariantAttribute::generate`] can be accumulated across many
+/// variants — some with a plain literal contribution, others depending on a child enum's
+/// `STEP_COUNT` — and spliced into `quote!` output as a single expression via [`ToTokens`],
+/// without the derive macro itself needing to evaluate it.
+#[derive(Clone, Default)]
+pub struct ExtendedSum {
+    constant: usize,
+    terms: Vec<TokenStream>,
+}
+
+impl ExtendedSum {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl std::ops::Add<usize> for ExtendedSum {
+    type Output = Self;
+
+    fn add(mut self, rhs: usize) -> Self {
+        self.constant += rhs;
+        self
+    }
+}
+
+impl std::ops::Add<TokenStream> for ExtendedSum {
+    type Output = Self;
+
+    fn add(mut self, rhs: TokenStream) -> Self {
+        self.terms.push(rhs);
+        self
+    }
+}
+
+impl ToTokens for ExtendedSum {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let constant = Literal::usize_unsuffixed(self.constant);
+        let terms = &self.terms;
+        tokens.extend(quote! { (#constant #(+ (#terms))*) });
+    }
+}
@@ -170,6 +170,7 @@ impl VariantAttribute {
 
     /// Generate the code for a single variant.
     /// Return the updated running tally of steps involved.
+    #[allow(clippy::too_many_arguments)]
     pub fn generate(
         &self,
         arm_count: &ExtendedSum,
@@ -178,6 +179,7 @@ impl VariantAttribute {
         as_ref_arms: &mut TokenStream,
         step_string_arms: &mut TokenStream,
         step_narrow_arms: &mut TokenStream,
+        step_index_from_str_arms: &mut TokenStream,
     ) -> ExtendedSum {
         if self.integer.is_none() {
             self.generate_single(
@@ -186,6 +188,7 @@ impl VariantAttribute {
                 as_ref_arms,
                 step_string_arms,
                 step_narrow_arms,
+                step_index_from_str_arms,
             )
         } else {
             self.generate_int(
@@ -195,10 +198,12 @@ impl VariantAttribute {
                 as_ref_arms,
                 step_string_arms,
                 step_narrow_arms,
+                step_index_from_str_arms,
             )
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn generate_single(
         &self,
         arm_count: &ExtendedSum,
@@ -206,6 +211,7 @@ impl VariantAttribute {
         as_ref_arms: &mut TokenStream,
         step_string_arms: &mut TokenStream,
         step_narrow_arms: &mut TokenStream,
+        step_index_from_str_arms: &mut TokenStream,
     ) -> ExtendedSum {
         // Unpack so that we can use `quote!()`.
         let VariantAttribute {
@@ -255,12 +261,32 @@ impl VariantAttribute {
                   => <#child as ::ipa_step::CompactStep>::step_narrow_type(i - (#next_arm_count)),
             });
 
+            // The inverse of the `step_string` arm above: a bare match on this variant's name
+            // recovers `#arm_count`, and a `name + "/" + rest` match recurses into the child,
+            // offsetting its answer by `#next_arm_count` the same way `step_string` does.
+            step_index_from_str_arms.extend(quote! {
+                if head == #step_name {
+                    return match tail {
+                        None => Some(#arm_count),
+                        Some(t) => <#child as ::ipa_step::CompactStep>::step_index_from_str(t)
+                          .map(|c| #next_arm_count + c),
+                    };
+                }
+            });
+
             range_end
         } else {
+            step_index_from_str_arms.extend(quote! {
+                if head == #step_name && tail.is_none() {
+                    return Some(#arm_count);
+                }
+            });
+
             next_arm_count
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn generate_int(
         &self,
         arm_count: &ExtendedSum,
@@ -269,6 +295,7 @@ impl VariantAttribute {
         as_ref_arms: &mut TokenStream,
         step_string_arms: &mut TokenStream,
         step_narrow_arms: &mut TokenStream,
+        step_index_from_str_arms: &mut TokenStream,
     ) -> ExtendedSum {
         // Unpack so that we can use `quote!()`.
         let VariantAttribute {
@@ -341,6 +368,22 @@ impl VariantAttribute {
                     }
                 }
             });
+
+            // Recover `i` by scanning `#array_name` for `head` (rather than re-deriving it from
+            // the zero-padding scheme `#array_name` was built with), then apply the same
+            // `divisor`-based offset arithmetic `step_string` above uses, in reverse.
+            step_index_from_str_arms.extend(quote! {
+                if let Some(i) = #array_name.iter().position(|n| *n == head) {
+                    let divisor = <#child as ::ipa_step::CompactStep>::STEP_COUNT + 1;
+                    let base = (#arm_count) + divisor * ::ipa_step::CompactGateIndex::try_from(i).unwrap();
+                    return match tail {
+                        None => Some(base),
+                        Some(t) => <#child as ::ipa_step::CompactStep>::step_index_from_str(t)
+                          .map(|c| base + 1 + c),
+                    };
+                }
+            });
+
             range_end
         } else {
             let idx =
@@ -353,6 +396,14 @@ impl VariantAttribute {
             step_string_arms.extend(quote! {
                 _ if i < #range_end => Self::#step_ident(#step_integer::try_from(i - (#arm_count)).unwrap()).as_ref().to_owned(),
             });
+
+            // Same scan-based recovery as the child-bearing arm above, without the child offset.
+            step_index_from_str_arms.extend(quote! {
+                if let Some(i) = #array_name.iter().position(|n| *n == head) {
+                    return tail.is_none().then(|| (#arm_count) + ::ipa_step::CompactGateIndex::try_from(i).unwrap());
+                }
+            });
+
             range_end
         }
     }
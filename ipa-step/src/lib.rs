@@ -6,6 +6,9 @@ pub mod descriptive;
 pub mod gate;
 #[cfg(feature = "name")]
 pub mod name;
+pub mod query;
+#[cfg(feature = "build")]
+pub mod schema;
 
 #[cfg(feature = "build")]
 pub use gate::build as build_gate;
@@ -63,6 +66,16 @@ pub trait CompactStep: Step {
     fn step_narrow_type(_i: usize) -> Option<&'static str> {
         None
     }
+
+    /// The inverse of [`step_string`](Self::step_string): recover the index for the step
+    /// named by `s`, which is expected to be in the same `"name"` or `"name/child/..."`
+    /// form that `step_string` produces.  Unlike `step_string`, which needs to scan at
+    /// most `STEP_COUNT` indices, this resolves in time proportional to the depth of `s`.
+    /// Returns `None` if `s` does not name a step in this hierarchy.
+    #[must_use]
+    fn step_index_from_str(_s: &str) -> Option<CompactGateIndex> {
+        None
+    }
 }
 
 /// A `Gate` implementation is a marker trait for a type that can be used to identify
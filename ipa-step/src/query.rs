@@ -0,0 +1,157 @@
+//! Path-pattern queries over a [`CompactStep`] hierarchy.
+//!
+//! The gate tree is effectively a hierarchical document of `/`-separated step
+//! names.  This module compiles a path pattern into a sequence of [`Segment`]s
+//! and enumerates the indices of every step whose string form matches it --
+//! useful for metrics filtering, tracing, and working out which
+//! multiplications ran under a subtree.
+
+use crate::CompactStep;
+
+/// One component of a compiled path pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// Match a single path component exactly.
+    Literal(String),
+    /// Match exactly one path component, whatever its value.
+    Any,
+    /// Match zero or more path components (the "descendants" axis).
+    Descendant,
+}
+
+/// A compiled path pattern, ready to be matched against step strings.
+///
+/// Patterns are `/`-separated, following the same form that
+/// [`CompactStep::step_string`] produces: `*` matches exactly one step level
+/// and `**` matches zero or more levels (including none at all), with any
+/// other segment matched literally.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern(Vec<Segment>);
+
+impl Pattern {
+    /// Compile a `/`-separated path pattern.
+    #[must_use]
+    pub fn parse(pattern: &str) -> Self {
+        Self(
+            pattern
+                .split('/')
+                .map(|s| match s {
+                    "*" => Segment::Any,
+                    "**" => Segment::Descendant,
+                    s => Segment::Literal(s.to_owned()),
+                })
+                .collect(),
+        )
+    }
+
+    /// Enumerate the indices and string forms of every step in `S` that
+    /// matches this pattern.
+    pub fn matching<S: CompactStep>(&self) -> impl Iterator<Item = (usize, String)> + '_ {
+        (0..S::STEP_COUNT).filter_map(move |i| {
+            let s = S::step_string(i);
+            let path = s.split('/').collect::<Vec<_>>();
+            self.matches(&path).then_some((i, s))
+        })
+    }
+
+    /// Check whether `path`, already split into its `/`-separated components,
+    /// matches this pattern.
+    ///
+    /// This is the classic glob two-pointer algorithm: `star_idx` tracks the
+    /// last `Descendant` segment seen and `match_idx` the path position it
+    /// last consumed up to, so that a later literal mismatch can backtrack
+    /// and let the descendant axis swallow one more component.
+    fn matches(&self, path: &[&str]) -> bool {
+        let (mut p, mut s) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while s < path.len() {
+            let advanced = match self.0.get(p) {
+                Some(Segment::Literal(l)) if l == path[s] => {
+                    p += 1;
+                    s += 1;
+                    true
+                }
+                Some(Segment::Any) => {
+                    p += 1;
+                    s += 1;
+                    true
+                }
+                Some(Segment::Descendant) => {
+                    backtrack = Some((p, s));
+                    p += 1;
+                    true
+                }
+                _ => false,
+            };
+
+            if !advanced {
+                let Some((star_idx, match_idx)) = backtrack else {
+                    return false;
+                };
+                let match_idx = match_idx + 1;
+                backtrack = Some((star_idx, match_idx));
+                p = star_idx + 1;
+                s = match_idx;
+            }
+        }
+
+        // A pattern that ends in one or more `**` still matches an empty tail.
+        while matches!(self.0.get(p), Some(Segment::Descendant)) {
+            p += 1;
+        }
+        p == self.0.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pattern, Segment};
+
+    #[test]
+    fn parses_segments() {
+        assert_eq!(
+            Pattern::parse("a/*/**/b").0,
+            vec![
+                Segment::Literal("a".to_owned()),
+                Segment::Any,
+                Segment::Descendant,
+                Segment::Literal("b".to_owned()),
+            ],
+        );
+    }
+
+    #[test]
+    fn literal_matches_exact_path() {
+        let p = Pattern::parse("sort/shuffle");
+        assert!(p.matches(&["sort", "shuffle"]));
+        assert!(!p.matches(&["sort", "shuffle", "extra"]));
+        assert!(!p.matches(&["sort"]));
+    }
+
+    #[test]
+    fn any_matches_one_level() {
+        let p = Pattern::parse("sort/*/multiply");
+        assert!(p.matches(&["sort", "shuffle", "multiply"]));
+        assert!(!p.matches(&["sort", "multiply"]));
+        assert!(!p.matches(&["sort", "shuffle", "extra", "multiply"]));
+    }
+
+    #[test]
+    fn descendant_matches_any_depth_including_empty() {
+        let p = Pattern::parse("sort/**/multiply");
+        assert!(p.matches(&["sort", "multiply"]));
+        assert!(p.matches(&["sort", "shuffle", "multiply"]));
+        assert!(p.matches(&["sort", "shuffle", "reveal", "multiply"]));
+        assert!(!p.matches(&["sort", "multiply", "extra"]));
+    }
+
+    #[test]
+    fn trailing_descendant_matches_remaining_tail() {
+        let p = Pattern::parse("sort/**");
+        assert!(p.matches(&["sort"]));
+        assert!(p.matches(&["sort", "shuffle"]));
+        assert!(p.matches(&["sort", "shuffle", "reveal"]));
+        assert!(!p.matches(&["other"]));
+    }
+}
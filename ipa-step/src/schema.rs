@@ -0,0 +1,378 @@
+//! A declarative description of a [`CompactStep`](crate::CompactStep) hierarchy,
+//! compiled into the same `enum` + `#[step(...)]` source that is otherwise
+//! hand-written and fed to `#[derive(CompactStep)]`.
+//!
+//! Hand-writing every step enum works, but it means the protocol's step tree is
+//! scattered across modules and each one has to get its `#[step(count/child/name)]`
+//! attributes right by hand.  A [`Schema`] lets that tree be described in one place
+//! (for example, parsed out of a schema file) and turned into generated source,
+//! using [`gate::build`](crate::gate::build)'s `COMPACT_GATE_INCLUDE` mechanism to
+//! fold the result into the crate that needs it.
+//!
+//! [`Schema::write_compact_gate_include`] is how a `build.rs` actually reaches
+//! [`gate::build`](crate::gate::build)'s convention: it validates the same
+//! constraints that `ipa_step_derive`'s `VariantAttribute` parser enforces
+//! (so a malformed schema is rejected here rather than producing a generated
+//! enum that then fails to derive), renders the schema, and writes the result
+//! to the path named by [`crate::COMPACT_GATE_INCLUDE_ENV`] -- the same file
+//! a hand-written `build.rs` points `COMPACT_GATE_INCLUDE` at, so the crate
+//! consuming it doesn't need to know whether its gate enums were hand-written
+//! or schema-generated.
+
+use std::{fmt, fs, io};
+
+/// A single node in a declarative step-tree schema: one step name, optionally
+/// repeated `count` times with an integer argument, optionally followed by a
+/// [`Schema`] describing what each of those steps narrows into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    /// The step name.  Used verbatim in `#[step(name = "...")]` and, once
+    /// sanitized into a valid identifier, becomes the variant name.
+    pub name: String,
+    /// If set, the step carries an integer argument ranging over `0..count`.
+    pub count: Option<usize>,
+    /// If set, the step narrows into this child hierarchy.
+    pub child: Option<Box<Schema>>,
+}
+
+impl Node {
+    /// A step with no integer argument and no children.
+    #[must_use]
+    pub fn leaf(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            count: None,
+            child: None,
+        }
+    }
+
+    /// Attach an integer argument ranging over `0..count`.
+    #[must_use]
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Attach a child hierarchy that each step in `self` narrows into.
+    #[must_use]
+    pub fn with_child(mut self, child: Schema) -> Self {
+        self.child = Some(Box::new(child));
+        self
+    }
+}
+
+/// A named collection of sibling [`Node`]s: one `#[derive(CompactStep)]` enum,
+/// with `enum_name` naming the generated type and `variants` its variants, in
+/// declaration order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    /// The name of the generated enum.
+    pub enum_name: String,
+    /// The variants of the generated enum, in declaration order.
+    pub variants: Vec<Node>,
+}
+
+/// A schema that fails the same constraints `#[derive(CompactStep)]` enforces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaError {
+    /// A step name contained a `/`, which is used as the path separator.
+    NameContainsSlash(String),
+    /// An integer variant's `count` was outside the `2..1000` range the derive allows.
+    CountOutOfRange(String, usize),
+    /// Two sibling nodes sanitize to the same variant identifier, which would
+    /// generate a duplicate enum variant.
+    DuplicateVariant(String, String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NameContainsSlash(name) => {
+                write!(f, "step name {name:?} cannot contain '/'")
+            }
+            Self::CountOutOfRange(name, count) => {
+                write!(
+                    f,
+                    "step {name:?} has count {count}, which is not in the range 2..1000"
+                )
+            }
+            Self::DuplicateVariant(a, b) => {
+                write!(
+                    f,
+                    "step names {a:?} and {b:?} both sanitize to the same variant identifier"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Errors from [`Schema::write_compact_gate_include`], on top of the [`SchemaError`]s
+/// [`Schema::validate`] already reports.
+#[derive(Debug)]
+pub enum BuildError {
+    /// The schema itself is malformed; see [`Schema::validate`].
+    Schema(SchemaError),
+    /// [`crate::COMPACT_GATE_INCLUDE_ENV`] isn't set in the build script's environment.
+    MissingEnv(&'static str),
+    /// The rendered source couldn't be written to the `COMPACT_GATE_INCLUDE` path.
+    Io(io::Error),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Schema(e) => write!(f, "{e}"),
+            Self::MissingEnv(var) => write!(f, "{var} is not set"),
+            Self::Io(e) => write!(f, "failed to write COMPACT_GATE_INCLUDE output: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<SchemaError> for BuildError {
+    fn from(e: SchemaError) -> Self {
+        Self::Schema(e)
+    }
+}
+
+fn validate_node(
+    node: &Node,
+    seen_idents: &mut std::collections::HashSet<String>,
+) -> Result<(), SchemaError> {
+    if node.name.contains('/') {
+        return Err(SchemaError::NameContainsSlash(node.name.clone()));
+    }
+    if let Some(count) = node.count {
+        if !(2..1000).contains(&count) {
+            return Err(SchemaError::CountOutOfRange(node.name.clone(), count));
+        }
+    }
+    let ident = sanitize_ident(&node.name);
+    if let Some(previous) = seen_idents.replace(ident) {
+        return Err(SchemaError::DuplicateVariant(previous, node.name.clone()));
+    }
+    if let Some(child) = &node.child {
+        child.validate()?;
+    }
+    Ok(())
+}
+
+/// Turn an arbitrary step or enum name into a valid `UpperCamelCase` Rust
+/// identifier, matching the convention used by this crate's hand-written step
+/// enums (`SortStep`, `ShuffleStep`, `ApplyInvStep`, ...): the name is split on
+/// non-alphanumeric characters, each resulting word is capitalized, and a
+/// leading digit (or an entirely empty name) gets a `_` prefix so the result
+/// always parses as an identifier.
+fn sanitize_ident(name: &str) -> String {
+    let mut ident = String::new();
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            ident.push(first.to_ascii_uppercase());
+            ident.extend(chars.map(|c| c.to_ascii_lowercase()));
+        }
+    }
+    if ident.chars().next().map_or(true, |c| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+impl Schema {
+    /// Check that every node in this schema, and in any nested child schema,
+    /// satisfies the constraints that `#[derive(CompactStep)]` enforces:
+    /// names without `/`, counts in `2..1000`, and sibling names that don't
+    /// collide once sanitized into variant identifiers.
+    ///
+    /// # Errors
+    /// Returns the first [`SchemaError`] encountered, in declaration order.
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        let mut seen_idents = std::collections::HashSet::new();
+        self.variants
+            .iter()
+            .try_for_each(|node| validate_node(node, &mut seen_idents))
+    }
+
+    /// Render this schema, and every child schema reachable from it, as the
+    /// enum definitions and `#[step(...)]` attributes that would otherwise be
+    /// hand-written, ready to be written to the file that `COMPACT_GATE_INCLUDE`
+    /// points at and compiled with `#[derive(CompactStep)]`.
+    #[must_use]
+    pub fn to_source(&self) -> String {
+        let mut out = String::new();
+        self.render_into(&mut out);
+        out
+    }
+
+    /// Validate this schema, render it, and write the result to the path named by
+    /// [`crate::COMPACT_GATE_INCLUDE_ENV`], so a `build.rs` can turn a [`Schema`] straight into
+    /// the crate's `COMPACT_GATE_INCLUDE` output in one call, the same way it would otherwise
+    /// hand-write that file.
+    ///
+    /// # Errors
+    /// Returns [`BuildError::Schema`] if this schema doesn't pass [`Self::validate`],
+    /// [`BuildError::MissingEnv`] if `COMPACT_GATE_INCLUDE` isn't set, or [`BuildError::Io`] if
+    /// the file can't be written.
+    pub fn write_compact_gate_include(&self) -> Result<(), BuildError> {
+        self.validate()?;
+        let path = std::env::var(crate::COMPACT_GATE_INCLUDE_ENV)
+            .map_err(|_| BuildError::MissingEnv(crate::COMPACT_GATE_INCLUDE_ENV))?;
+        fs::write(path, self.to_source()).map_err(BuildError::Io)
+    }
+
+    fn render_into(&self, out: &mut String) {
+        let enum_ident = sanitize_ident(&self.enum_name);
+        out.push_str(&format!(
+            "#[derive(::ipa_step_derive::CompactStep)]\npub enum {enum_ident} {{\n"
+        ));
+        for variant in &self.variants {
+            out.push_str(&render_variant(variant));
+        }
+        out.push_str("}\n");
+
+        // Child enums must be defined somewhere for the `child = ...` type
+        // references above to resolve, so emit each one after its parent.
+        for variant in &self.variants {
+            if let Some(child) = &variant.child {
+                child.render_into(out);
+            }
+        }
+    }
+}
+
+fn render_variant(node: &Node) -> String {
+    let ident = sanitize_ident(&node.name);
+    let mut attr = format!("name = \"{}\"", node.name);
+    if let Some(child) = &node.child {
+        attr.push_str(&format!(", child = {}", sanitize_ident(&child.enum_name)));
+    }
+
+    if let Some(count) = node.count {
+        format!("    #[step({attr}, count = {count})]\n    {ident}(u32),\n")
+    } else {
+        format!("    #[step({attr})]\n    {ident},\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BuildError, Node, Schema, SchemaError};
+
+    #[test]
+    fn validates_clean_schema() {
+        let schema = Schema {
+            enum_name: "SortStep".to_owned(),
+            variants: vec![Node::leaf("apply").with_count(16)],
+        };
+        assert_eq!(schema.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_slash_in_name() {
+        let schema = Schema {
+            enum_name: "BadStep".to_owned(),
+            variants: vec![Node::leaf("a/b")],
+        };
+        assert_eq!(
+            schema.validate(),
+            Err(SchemaError::NameContainsSlash("a/b".to_owned()))
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_count() {
+        let schema = Schema {
+            enum_name: "BadStep".to_owned(),
+            variants: vec![Node::leaf("apply").with_count(1)],
+        };
+        assert_eq!(
+            schema.validate(),
+            Err(SchemaError::CountOutOfRange("apply".to_owned(), 1))
+        );
+    }
+
+    #[test]
+    fn validates_nested_child() {
+        let schema = Schema {
+            enum_name: "OuterStep".to_owned(),
+            variants: vec![Node::leaf("outer").with_child(Schema {
+                enum_name: "InnerStep".to_owned(),
+                variants: vec![Node::leaf("inner/bad")],
+            })],
+        };
+        assert_eq!(
+            schema.validate(),
+            Err(SchemaError::NameContainsSlash("inner/bad".to_owned()))
+        );
+    }
+
+    #[test]
+    fn renders_child_enum_definition() {
+        let schema = Schema {
+            enum_name: "OuterStep".to_owned(),
+            variants: vec![Node::leaf("outer").with_child(Schema {
+                enum_name: "InnerStep".to_owned(),
+                variants: vec![Node::leaf("inner")],
+            })],
+        };
+        let source = schema.to_source();
+        assert!(source.contains("pub enum OuterStep"));
+        assert!(source.contains("child = InnerStep"));
+        assert!(source.contains("pub enum InnerStep"));
+    }
+
+    #[test]
+    fn sanitizes_unusual_names_into_valid_identifiers() {
+        let schema = Schema {
+            enum_name: "3d-transform".to_owned(),
+            variants: vec![Node::leaf("3d-transform")],
+        };
+        assert_eq!(schema.validate(), Ok(()));
+        let source = schema.to_source();
+        assert!(source.contains("pub enum _3dTransform"));
+        assert!(source.contains("_3dTransform,"));
+    }
+
+    #[test]
+    fn rejects_duplicate_sibling_idents() {
+        let schema = Schema {
+            enum_name: "BadStep".to_owned(),
+            variants: vec![Node::leaf("foo bar"), Node::leaf("foo-bar")],
+        };
+        assert_eq!(
+            schema.validate(),
+            Err(SchemaError::DuplicateVariant(
+                "foo bar".to_owned(),
+                "foo-bar".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn write_compact_gate_include_writes_rendered_source_to_env_path() {
+        let schema = Schema {
+            enum_name: "SortStep".to_owned(),
+            variants: vec![Node::leaf("apply").with_count(16)],
+        };
+        let path = std::env::temp_dir().join("schema_write_compact_gate_include_test.rs");
+
+        std::env::remove_var(crate::COMPACT_GATE_INCLUDE_ENV);
+        assert!(matches!(
+            schema.write_compact_gate_include(),
+            Err(BuildError::MissingEnv(crate::COMPACT_GATE_INCLUDE_ENV))
+        ));
+
+        std::env::set_var(crate::COMPACT_GATE_INCLUDE_ENV, &path);
+        schema
+            .write_compact_gate_include()
+            .expect("env var is set and the path is writable");
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, schema.to_source());
+
+        std::env::remove_var(crate::COMPACT_GATE_INCLUDE_ENV);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,4 +1,7 @@
-use std::{io, io::Write};
+use std::{
+    io,
+    io::{BufRead, Write},
+};
 
 pub trait Serializer {
     /// Converts self into a CSV-encoded byte string
@@ -7,6 +10,23 @@ pub trait Serializer {
     fn to_csv<W: Write>(&self, buf: &mut W) -> io::Result<()>;
 }
 
+/// The inverse of [`Serializer`]: parses a value back out of the CSV encoding
+/// that [`Serializer::to_csv`] produces.
+pub trait Deserializer: Sized {
+    /// Parses a single CSV-encoded record, read as one line from `buf`.
+    /// ## Errors
+    /// If `buf` does not contain a well-formed record, or reading from it fails.
+    fn from_csv<R: BufRead>(buf: &mut R) -> io::Result<Self>;
+
+    /// Parses a stream of CSV-encoded records, one per line.
+    fn read_records<R: BufRead>(r: R) -> impl Iterator<Item = io::Result<Self>> {
+        r.lines().map(|line| {
+            let mut cursor = io::Cursor::new(line?.into_bytes());
+            Self::from_csv(&mut cursor)
+        })
+    }
+}
+
 #[cfg(any(test, feature = "test-fixture"))]
 impl Serializer for crate::test_fixture::ipa::TestRawDataRecord {
     fn to_csv<W: Write>(&self, buf: &mut W) -> io::Result<()> {
@@ -20,3 +40,105 @@ impl Serializer for crate::test_fixture::ipa::TestRawDataRecord {
         Ok(())
     }
 }
+
+#[cfg(any(test, feature = "test-fixture"))]
+impl Deserializer for crate::test_fixture::ipa::TestRawDataRecord {
+    fn from_csv<R: BufRead>(buf: &mut R) -> io::Result<Self> {
+        fn bad_field(field: &str, value: &str) -> io::Error {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid `{field}` value {value:?} in CSV record"),
+            )
+        }
+
+        let mut line = String::new();
+        buf.read_line(&mut line)?;
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        let fields: Vec<&str> = line.split(',').collect();
+        let [timestamp, user_id, is_trigger_report, breakdown_key, trigger_value] = fields[..]
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected 5 comma-separated fields in CSV record, found {}: {line:?}",
+                    fields.len()
+                ),
+            ));
+        };
+
+        let is_trigger_report = match is_trigger_report {
+            "0" => false,
+            "1" => true,
+            v => return Err(bad_field("is_trigger_report", v)),
+        };
+
+        Ok(Self {
+            timestamp: timestamp
+                .parse()
+                .map_err(|_| bad_field("timestamp", timestamp))?,
+            user_id: user_id.parse().map_err(|_| bad_field("user_id", user_id))?,
+            is_trigger_report,
+            breakdown_key: breakdown_key
+                .parse()
+                .map_err(|_| bad_field("breakdown_key", breakdown_key))?,
+            trigger_value: trigger_value
+                .parse()
+                .map_err(|_| bad_field("trigger_value", trigger_value))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng};
+    use rand_core::SeedableRng;
+
+    use super::{Deserializer, Serializer};
+    use crate::test_fixture::ipa::TestRawDataRecord;
+
+    fn random_record(rng: &mut StdRng) -> TestRawDataRecord {
+        TestRawDataRecord {
+            timestamp: rng.gen(),
+            user_id: rng.gen(),
+            is_trigger_report: rng.gen(),
+            breakdown_key: rng.gen(),
+            trigger_value: rng.gen(),
+        }
+    }
+
+    #[test]
+    fn csv_roundtrip() {
+        let mut rng = StdRng::from_seed([1_u8; 32]);
+
+        for _ in 0..100 {
+            let record = random_record(&mut rng);
+
+            let mut buf = Vec::new();
+            record.to_csv(&mut buf).unwrap();
+
+            let mut cursor = std::io::Cursor::new(buf);
+            let parsed = TestRawDataRecord::from_csv(&mut cursor).unwrap();
+
+            assert_eq!(parsed, record);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_field_count() {
+        let mut cursor = std::io::Cursor::new(b"1,2,0,3".to_vec());
+        assert!(TestRawDataRecord::from_csv(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn rejects_non_integer_field() {
+        let mut cursor = std::io::Cursor::new(b"1,2,0,3,not_a_number".to_vec());
+        assert!(TestRawDataRecord::from_csv(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_is_trigger_report() {
+        let mut cursor = std::io::Cursor::new(b"1,2,2,3,4".to_vec());
+        assert!(TestRawDataRecord::from_csv(&mut cursor).is_err());
+    }
+}
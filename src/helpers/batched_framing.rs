@@ -0,0 +1,147 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    num::NonZeroUsize,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+use generic_array::GenericArray;
+use typenum::Unsigned;
+
+use crate::ff::Serializable;
+
+/// Adapts a `Stream` of individually-ordered `T` records (e.g. the output of
+/// [`crate::helpers::ordering_mpsc::OrderingMpscReceiver`]) into a `Stream<Item = Vec<u8>>` of
+/// length-prefixed batches suitable for [`crate::helpers::Transport::send`], amortizing the
+/// per-message transport overhead across many records the same way Packet-MMAP amortizes
+/// per-packet overhead by batching many packets per syscall on a raw socket.
+///
+/// Each batch is a 4-byte little-endian record count followed by that many fixed-size
+/// `T::serialize` outputs back to back. A batch is flushed once it reaches `max_batch` records,
+/// as soon as the underlying stream has no further record *immediately* ready (so a slow
+/// producer's next record isn't held up waiting to fill a batch that may not fill for a while),
+/// or once the underlying stream ends. [`DeframedReceiver`] is the matching de-framing step on
+/// the receiving side.
+pub struct BatchedSender<T, St> {
+    inner: St,
+    max_batch: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T, St> BatchedSender<T, St>
+where
+    St: Stream<Item = T> + Unpin,
+{
+    #[must_use]
+    pub fn new(inner: St, max_batch: NonZeroUsize) -> Self {
+        Self {
+            inner,
+            max_batch: max_batch.get(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, St> Stream for BatchedSender<T, St>
+where
+    T: Serializable,
+    St: Stream<Item = T> + Unpin,
+{
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut records = Vec::new();
+        let ended = loop {
+            if records.len() >= self.max_batch {
+                break false;
+            }
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(record)) => records.push(record),
+                Poll::Ready(None) => break true,
+                Poll::Pending => break false,
+            }
+        };
+
+        if records.is_empty() {
+            return if ended {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            };
+        }
+
+        let record_size = <T as Serializable>::Size::to_usize();
+        let mut framed = Vec::with_capacity(4 + records.len() * record_size);
+        framed.extend_from_slice(&u32::try_from(records.len()).unwrap().to_le_bytes());
+        for record in &records {
+            let mut buf = GenericArray::default();
+            record.serialize(&mut buf);
+            framed.extend_from_slice(&buf);
+        }
+        Poll::Ready(Some(framed))
+    }
+}
+
+/// The inverse of [`BatchedSender`]: reassembles a `Stream<Item = Vec<u8>>` of raw transport
+/// chunks (which need not land on batch boundaries - a [`crate::helpers::quic_transport`] frame
+/// or an `InMemoryStream` codec chunk can split a batch across several reads, or fit several
+/// batches in one) back into one `Vec<u8>` per individual fixed-size record, so downstream
+/// consumers see exactly the same per-record granularity and order as they would without
+/// batching.
+pub struct DeframedReceiver<St> {
+    inner: St,
+    record_size: usize,
+    buf: Vec<u8>,
+    /// Records already split out of a fully-buffered batch, awaiting delivery one at a time.
+    pending: VecDeque<Vec<u8>>,
+}
+
+impl<St> DeframedReceiver<St>
+where
+    St: Stream<Item = Vec<u8>> + Unpin,
+{
+    #[must_use]
+    pub fn new(inner: St, record_size: usize) -> Self {
+        Self {
+            inner,
+            record_size,
+            buf: Vec::new(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<St> Stream for DeframedReceiver<St>
+where
+    St: Stream<Item = Vec<u8>> + Unpin,
+{
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Poll::Ready(Some(record));
+            }
+
+            if self.buf.len() >= 4 {
+                let count = u32::from_le_bytes(self.buf[..4].try_into().unwrap()) as usize;
+                let needed = 4 + count * self.record_size;
+                if self.buf.len() >= needed {
+                    let batch: Vec<u8> = self.buf.drain(..needed).collect();
+                    for record in batch[4..].chunks_exact(self.record_size) {
+                        self.pending.push_back(record.to_vec());
+                    }
+                    continue;
+                }
+            }
+
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(bytes)) => self.buf.extend_from_slice(&bytes),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
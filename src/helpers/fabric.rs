@@ -1,9 +1,10 @@
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use crate::protocol::{RecordId, Step};
 use async_trait::async_trait;
 use crate::helpers::error::Error;
 use crate::helpers::Identity;
 use crate::test_fixture;
+use std::collections::HashMap;
 
 /// Combination of helper identity and step that uniquely identifies a single channel of communication
 /// between two helpers.
@@ -28,6 +29,158 @@ pub trait Fabric<S> {
 
     async fn get_connection(&self, addr: ChannelId<S>) -> Self::Channel;
     fn message_stream(&self) -> Self::MessageStream;
+
+    /// The full set of parties reachable on this fabric, including `self`. Needed to compute
+    /// the `(n + f) / 2` / `2f + 1` thresholds that [`Self::reliable_broadcast`] waits for.
+    fn identities(&self) -> Vec<Identity>;
+
+    /// This party's own identity, used to tell apart "I am the sender" from "I am a receiver" in
+    /// [`Self::reliable_broadcast`].
+    fn own_identity(&self) -> Identity;
+
+    /// Bracha's reliable broadcast: deliver `payload` (when `self.own_identity()` is the sender
+    /// identified by `channel.identity`) or receive it (otherwise) such that every honest party
+    /// delivers the same value, even if the sender is malicious and equivocates.
+    ///
+    /// This runs the classic three-phase echo/ready protocol: the sender sends `(INIT, v)` to
+    /// everyone; on first receipt of a value (via `INIT` or enough matching `ECHO`/`READY`) a
+    /// party sends `(ECHO, v)`; once a party has seen `v` echoed (or readied) by enough peers it
+    /// sends `(READY, v)`; once a party has seen enough `READY`s for `v`, it delivers `v`.
+    ///
+    /// Each phase is sent on its own sub-step of `channel.step` (see [`BroadcastStep`]), and
+    /// messages are self-describing (tagged with phase and sender) so that they can be told apart
+    /// on [`Self::message_stream`], which does not otherwise carry that information.
+    ///
+    /// Like the rest of this crate's protocols, this targets the honest-majority setting
+    /// (`n = 2f + 1`, i.e. three helpers tolerating one corrupt party) rather than the stronger
+    /// `n = 3f + 1` needed for worst-case asynchronous Byzantine agreement: a matching `ECHO` (or
+    /// `READY`) from `f + 1` distinct parties is enough to advance, and `2f + 1` matching
+    /// `READY`s deliver. For three helpers that's: echo/ready-amplification threshold 2, deliver
+    /// threshold 3.
+    ///
+    /// Requiring all `n` parties' `READY`s (rather than some smaller quorum) is what keeps this
+    /// safe even against a sender that equivocates throughout `ECHO`/`READY`, not just at
+    /// `INIT`: two honest parties can each independently collect `f + 1` matching `READY`s for
+    /// *different* values (their own plus the corrupt sender's), but since every party is needed
+    /// to cross the deliver threshold, at least one of those `READY` sets must include a second
+    /// honest party, and an honest party only ever readies one value. The cost is liveness: a
+    /// faulty party that is merely crashed or unresponsive (rather than actively malicious) can
+    /// block delivery forever, since there is no quorum below `n`.
+    ///
+    /// # Errors
+    /// Returns an error if sending to any peer fails.
+    async fn reliable_broadcast(&self, channel: ChannelId<S>, payload: Box<[u8]>) -> Result<Box<[u8]>, Error>
+    where
+        S: Step + Send + Sync + Clone,
+        Self: Sized + Sync,
+    {
+        let peers = self.identities();
+        let n = peers.len();
+        let f = (n - 1) / 2;
+        let amplify_threshold = f + 1;
+        let deliver_threshold = 2 * f + 1;
+
+        let record_id = RecordId::from(0_usize);
+        let mut incoming = self.message_stream();
+
+        let mut echoed = false;
+        let mut readied = false;
+        let mut delivered_value: Option<Box<[u8]>> = None;
+        let mut echoes: HashMap<Box<[u8]>, Vec<Identity>> = HashMap::new();
+        let mut readies: HashMap<Box<[u8]>, Vec<Identity>> = HashMap::new();
+
+        if channel.identity == self.own_identity() {
+            self.send_to_all(&peers, &channel.step, BroadcastPhase::Init, &payload)
+                .await?;
+        }
+
+        while delivered_value.is_none() {
+            let Some(chunk) = incoming.next().await else {
+                break;
+            };
+            for envelope in chunk {
+                if envelope.record_id != record_id {
+                    continue;
+                }
+                let Some(msg) = BroadcastMessage::decode(&envelope.payload) else {
+                    continue;
+                };
+
+                match msg.phase {
+                    BroadcastPhase::Init => {
+                        if msg.sender == channel.identity && !echoed {
+                            echoed = true;
+                            self.send_to_all(&peers, &channel.step, BroadcastPhase::Echo, &msg.value)
+                                .await?;
+                        }
+                    }
+                    BroadcastPhase::Echo => {
+                        let seen = echoes.entry(msg.value.clone()).or_default();
+                        if !seen.contains(&msg.sender) {
+                            seen.push(msg.sender);
+                        }
+                        if !echoed && seen.len() >= amplify_threshold {
+                            echoed = true;
+                            self.send_to_all(&peers, &channel.step, BroadcastPhase::Echo, &msg.value)
+                                .await?;
+                        }
+                        if !readied && seen.len() >= amplify_threshold {
+                            readied = true;
+                            self.send_to_all(&peers, &channel.step, BroadcastPhase::Ready, &msg.value)
+                                .await?;
+                        }
+                    }
+                    BroadcastPhase::Ready => {
+                        let seen = readies.entry(msg.value.clone()).or_default();
+                        if !seen.contains(&msg.sender) {
+                            seen.push(msg.sender);
+                        }
+                        if !readied && seen.len() >= amplify_threshold {
+                            readied = true;
+                            self.send_to_all(&peers, &channel.step, BroadcastPhase::Ready, &msg.value)
+                                .await?;
+                        }
+                        if seen.len() >= deliver_threshold {
+                            delivered_value = Some(msg.value);
+                        }
+                    }
+                }
+            }
+        }
+
+        delivered_value.ok_or(Error::ReliableBroadcastFailed)
+    }
+
+    /// Send one Bracha broadcast phase message to every peer, including `self`.
+    async fn send_to_all(
+        &self,
+        peers: &[Identity],
+        step: &S,
+        phase: BroadcastPhase,
+        value: &[u8],
+    ) -> Result<(), Error>
+    where
+        S: Step + Clone,
+        Self: Sized,
+    {
+        let message = BroadcastMessage {
+            phase,
+            sender: self.own_identity(),
+            value: value.into(),
+        }
+        .encode();
+        for &peer in peers {
+            let addr = ChannelId::new(peer, BroadcastStep::new(step.clone(), phase));
+            self.get_connection(addr)
+                .await
+                .send(MessageEnvelope {
+                    record_id: RecordId::from(0_usize),
+                    payload: message.clone(),
+                })
+                .await?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -44,3 +197,293 @@ impl <S: Step> ChannelId<S> {
     }
 }
 
+/// The three phases of Bracha's reliable broadcast protocol.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum BroadcastPhase {
+    Init,
+    Echo,
+    Ready,
+}
+
+impl AsRef<str> for BroadcastPhase {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Init => "init",
+            Self::Echo => "echo",
+            Self::Ready => "ready",
+        }
+    }
+}
+
+/// A sub-step of some parent step `S`, identifying one phase of a [`Fabric::reliable_broadcast`]
+/// run. Distinct phases get distinct channels so that, on fabrics where `message_stream` can be
+/// demultiplexed by channel, `INIT`/`ECHO`/`READY` traffic never collides.
+#[derive(Clone, Debug)]
+pub struct BroadcastStep<S> {
+    parent: S,
+    phase: BroadcastPhase,
+    label: String,
+}
+
+impl<S: Step> BroadcastStep<S> {
+    #[must_use]
+    pub fn new(parent: S, phase: BroadcastPhase) -> Self {
+        let label = format!("{}/{}", parent.as_ref(), phase.as_ref());
+        Self { parent, phase, label }
+    }
+
+    #[must_use]
+    pub fn parent(&self) -> &S {
+        &self.parent
+    }
+
+    #[must_use]
+    pub fn phase(&self) -> BroadcastPhase {
+        self.phase
+    }
+}
+
+impl<S: Step> AsRef<str> for BroadcastStep<S> {
+    fn as_ref(&self) -> &str {
+        &self.label
+    }
+}
+
+impl<S: Step> Step for BroadcastStep<S> {}
+
+impl<S: PartialEq> PartialEq for BroadcastStep<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parent == other.parent && self.phase == other.phase
+    }
+}
+
+impl<S: Eq> Eq for BroadcastStep<S> {}
+
+impl<S: std::hash::Hash> std::hash::Hash for BroadcastStep<S> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.parent.hash(state);
+        self.phase.hash(state);
+    }
+}
+
+/// The wire format for one Bracha broadcast phase message: which phase it belongs to, who sent
+/// it, and the value being echoed/readied. Carried in [`MessageEnvelope::payload`] since
+/// [`Fabric::message_stream`] gives no other way to recover this information on receipt.
+struct BroadcastMessage {
+    phase: BroadcastPhase,
+    sender: Identity,
+    value: Box<[u8]>,
+}
+
+impl BroadcastMessage {
+    fn encode(&self) -> Box<[u8]> {
+        let phase_byte = match self.phase {
+            BroadcastPhase::Init => 0_u8,
+            BroadcastPhase::Echo => 1_u8,
+            BroadcastPhase::Ready => 2_u8,
+        };
+        let sender_byte = match self.sender {
+            Identity::H1 => 0_u8,
+            Identity::H2 => 1_u8,
+            Identity::H3 => 2_u8,
+        };
+        let mut bytes = Vec::with_capacity(2 + self.value.len());
+        bytes.push(phase_byte);
+        bytes.push(sender_byte);
+        bytes.extend_from_slice(&self.value);
+        bytes.into_boxed_slice()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let (&phase_byte, rest) = bytes.split_first()?;
+        let phase = match phase_byte {
+            0 => BroadcastPhase::Init,
+            1 => BroadcastPhase::Echo,
+            2 => BroadcastPhase::Ready,
+            _ => return None,
+        };
+        let (&sender_byte, value) = rest.split_first()?;
+        let sender = match sender_byte {
+            0 => Identity::H1,
+            1 => Identity::H2,
+            2 => Identity::H3,
+            _ => return None,
+        };
+        Some(Self {
+            phase,
+            sender,
+            value: value.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod reliable_broadcast_tests {
+    use super::*;
+    use futures::stream::BoxStream;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+    struct TestStep;
+
+    impl AsRef<str> for TestStep {
+        fn as_ref(&self) -> &str {
+            "reliable-broadcast-test"
+        }
+    }
+
+    impl Step for TestStep {}
+
+    struct MockChannel {
+        sender: mpsc::UnboundedSender<MessageEnvelope>,
+    }
+
+    #[async_trait]
+    impl CommunicationChannel for MockChannel {
+        async fn send(&self, msg: MessageEnvelope) -> Result<(), Error> {
+            self.sender
+                .send(msg)
+                .map_err(|_send_error| Error::ReliableBroadcastFailed)
+        }
+    }
+
+    /// A fabric for one of the three helpers in the test network: it can reach every helper's
+    /// inbox (including its own) and owns its own inbox's receiving end.
+    struct MockFabric {
+        me: Identity,
+        inboxes: HashMap<Identity, mpsc::UnboundedSender<MessageEnvelope>>,
+        inbox: std::sync::Mutex<Option<mpsc::UnboundedReceiver<MessageEnvelope>>>,
+    }
+
+    #[async_trait]
+    impl Fabric<BroadcastStep<TestStep>> for MockFabric {
+        type Channel = MockChannel;
+        type MessageStream = BoxStream<'static, MessageChunks>;
+
+        async fn get_connection(&self, addr: ChannelId<BroadcastStep<TestStep>>) -> Self::Channel {
+            MockChannel {
+                sender: self.inboxes[&addr.identity].clone(),
+            }
+        }
+
+        fn message_stream(&self) -> Self::MessageStream {
+            let receiver = self.inbox.lock().unwrap().take().expect("message_stream called twice");
+            Box::pin(UnboundedReceiverStream::new(receiver).map(|envelope| vec![envelope]))
+        }
+
+        fn identities(&self) -> Vec<Identity> {
+            vec![Identity::H1, Identity::H2, Identity::H3]
+        }
+
+        fn own_identity(&self) -> Identity {
+            self.me
+        }
+    }
+
+    fn network() -> (MockFabric, MockFabric, MockFabric) {
+        let (tx1, rx1) = mpsc::unbounded_channel();
+        let (tx2, rx2) = mpsc::unbounded_channel();
+        let (tx3, rx3) = mpsc::unbounded_channel();
+        let inboxes = HashMap::from([
+            (Identity::H1, tx1),
+            (Identity::H2, tx2),
+            (Identity::H3, tx3),
+        ]);
+        let fabric = |me, inbox| MockFabric {
+            me,
+            inboxes: inboxes.clone(),
+            inbox: std::sync::Mutex::new(Some(inbox)),
+        };
+        (
+            fabric(Identity::H1, rx1),
+            fabric(Identity::H2, rx2),
+            fabric(Identity::H3, rx3),
+        )
+    }
+
+    async fn inject(fabric: &MockFabric, to: Identity, phase: BroadcastPhase, value: &[u8]) {
+        let message = BroadcastMessage {
+            phase,
+            sender: fabric.own_identity(),
+            value: value.into(),
+        }
+        .encode();
+        let step = BroadcastStep::new(TestStep, phase);
+        fabric
+            .get_connection(ChannelId::new(to, step))
+            .await
+            .send(MessageEnvelope {
+                record_id: RecordId::from(0_usize),
+                payload: message,
+            })
+            .await
+            .unwrap();
+    }
+
+    /// H1 equivocates: it tells H2 the value is `VALUE_A` and H3 the value is `VALUE_B`. It then
+    /// plays along honestly with the rest of the protocol for `VALUE_A`, which is enough for the
+    /// two honest helpers (H2, H3) to reach agreement on `VALUE_A` despite the conflicting INITs.
+    #[tokio::test]
+    async fn honest_parties_agree_despite_equivocating_sender() {
+        const VALUE_A: &[u8] = b"value-a";
+        const VALUE_B: &[u8] = b"value-b";
+
+        let (h1, h2, h3) = network();
+
+        inject(&h1, Identity::H2, BroadcastPhase::Init, VALUE_A).await;
+        inject(&h1, Identity::H3, BroadcastPhase::Init, VALUE_B).await;
+        inject(&h1, Identity::H2, BroadcastPhase::Echo, VALUE_A).await;
+        inject(&h1, Identity::H3, BroadcastPhase::Echo, VALUE_A).await;
+        inject(&h1, Identity::H2, BroadcastPhase::Ready, VALUE_A).await;
+        inject(&h1, Identity::H3, BroadcastPhase::Ready, VALUE_A).await;
+
+        let channel_for = || ChannelId::new(Identity::H1, BroadcastStep::new(TestStep, BroadcastPhase::Init));
+        let (delivered_to_h2, delivered_to_h3) = tokio::join!(
+            h2.reliable_broadcast(channel_for(), Box::from(&[][..])),
+            h3.reliable_broadcast(channel_for(), Box::from(&[][..])),
+        );
+
+        let delivered_to_h2 = delivered_to_h2.unwrap();
+        let delivered_to_h3 = delivered_to_h3.unwrap();
+        assert_eq!(&*delivered_to_h2, VALUE_A);
+        assert_eq!(delivered_to_h2, delivered_to_h3);
+    }
+
+    /// H1 equivocates for the entire protocol, not just `INIT`: it tells H2 the value is
+    /// `VALUE_A` and plays along honestly with H2 on `VALUE_A` through `ECHO`/`READY`, while
+    /// telling H3 the value is `VALUE_B` and playing along honestly with H3 on `VALUE_B` the
+    /// same way. With a deliver threshold of `2f + 1 = n`, neither H2 nor H3 can ever collect a
+    /// `READY` from all three parties for their respective value (the other honest party never
+    /// readies it), so both correctly block rather than deliver disagreeing values.
+    #[tokio::test]
+    async fn honest_parties_never_disagree_despite_sustained_equivocation() {
+        const VALUE_A: &[u8] = b"value-a";
+        const VALUE_B: &[u8] = b"value-b";
+
+        let (h1, h2, h3) = network();
+
+        inject(&h1, Identity::H2, BroadcastPhase::Init, VALUE_A).await;
+        inject(&h1, Identity::H3, BroadcastPhase::Init, VALUE_B).await;
+        inject(&h1, Identity::H2, BroadcastPhase::Echo, VALUE_A).await;
+        inject(&h1, Identity::H3, BroadcastPhase::Echo, VALUE_B).await;
+        inject(&h1, Identity::H2, BroadcastPhase::Ready, VALUE_A).await;
+        inject(&h1, Identity::H3, BroadcastPhase::Ready, VALUE_B).await;
+
+        let channel_for = || ChannelId::new(Identity::H1, BroadcastStep::new(TestStep, BroadcastPhase::Init));
+        let result = tokio::time::timeout(
+            std::time::Duration::from_millis(200),
+            tokio::join!(
+                h2.reliable_broadcast(channel_for(), Box::from(&[][..])),
+                h3.reliable_broadcast(channel_for(), Box::from(&[][..])),
+            ),
+        )
+        .await;
+
+        assert!(
+            result.is_err(),
+            "neither honest party should ever reach its deliver threshold, \
+             since doing so independently would mean they delivered different values"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Transport-layer primitives shared by every backend in this directory: the QUIC transport
+//! ([`quic_transport`]) and the in-memory one under `crate::test_fixture::network`, plus the
+//! ordering/framing building blocks ([`ordering_mpsc`], [`sharded_ordering_mpsc`],
+//! [`batched_framing`]) they're built on.
+
+pub mod batched_framing;
+pub mod fabric;
+pub mod ordering_mpsc;
+pub mod quic_transport;
+pub mod sharded_ordering_mpsc;
+
+use std::borrow::Borrow;
+
+use async_trait::async_trait;
+use futures::Stream;
+
+use crate::protocol::{QueryId, Step};
+
+/// One of the helper parties participating in a query, 1-indexed to match the convention used
+/// throughout the wire protocol and config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HelperIdentity(u8);
+
+impl HelperIdentity {
+    pub const ONE: Self = Self(1);
+    pub const TWO: Self = Self(2);
+    pub const THREE: Self = Self(3);
+}
+
+impl TryFrom<usize> for HelperIdentity {
+    type Error = String;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match u8::try_from(value) {
+            Ok(v @ 1..=3) => Ok(Self(v)),
+            _ => Err(format!("{value} is not a valid helper index (expected 1..=3)")),
+        }
+    }
+}
+
+/// The route a [`Transport::send`]/[`Transport::receive`] call is addressed to, i.e. which
+/// handler on the receiving side should process it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteId {
+    ReceiveQuery,
+    PrepareQuery,
+    Records,
+    /// A peer authenticating itself before any other route is accepted. See
+    /// [`TransportCallbacks::authenticate`].
+    Handshake,
+}
+
+/// Marker resource identifier for routes that aren't addressed to any particular [`RouteId`]
+/// (currently just [`Transport::receive`], which always pulls from the `Records` stream for its
+/// `(query_id, step)` key, regardless of route).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoResourceIdentifier;
+
+/// Implemented by the possible shapes of the query id a [`RouteParams`] carries: a concrete
+/// [`QueryId`] (most routes) or none at all (e.g. `ReceiveQuery`, which doesn't have one yet).
+pub trait QueryIdBinding: Send {}
+impl QueryIdBinding for QueryId {}
+impl QueryIdBinding for Option<QueryId> {}
+
+/// Implemented by the possible shapes of the step a [`RouteParams`] carries, mirroring
+/// [`QueryIdBinding`].
+pub trait StepBinding: Send {}
+impl StepBinding for Step {}
+impl StepBinding for Option<Step> {}
+
+/// What a [`Transport::send`]/[`Transport::receive`] route carries: which handler it's addressed
+/// to (`I`), its query id (`Q`) and step (`S`) if any, and an arbitrary payload (`extra`) carried
+/// alongside them.
+pub trait RouteParams<I, Q: QueryIdBinding, S: StepBinding>: Send + Sync {
+    type Params: Borrow<str>;
+
+    fn resource_identifier(&self) -> I;
+    fn query_id(&self) -> Q;
+    fn step(&self) -> S;
+    fn extra(&self) -> Self::Params;
+}
+
+impl RouteParams<NoResourceIdentifier, QueryId, Step> for (QueryId, Step) {
+    type Params = &'static str;
+
+    fn resource_identifier(&self) -> NoResourceIdentifier {
+        NoResourceIdentifier
+    }
+
+    fn query_id(&self) -> QueryId {
+        self.0
+    }
+
+    fn step(&self) -> Step {
+        self.1.clone()
+    }
+
+    fn extra(&self) -> Self::Params {
+        ""
+    }
+}
+
+/// Failure modes a [`Transport`] backend can report back to its caller.
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    #[error("transport rejected a message bound for {dest:?}: {inner}")]
+    Rejected { dest: HelperIdentity, inner: String },
+    #[error("I/O error: {inner}")]
+    Io { inner: std::io::Error },
+}
+
+/// A transport capable of sending to, and receiving record streams from, other helpers. Each
+/// backend (QUIC, in-memory) implements this once for `Weak<Self>`, so a transport can hand out
+/// shared references to itself (e.g. to a [`TransportCallbacks`] handler) without creating a
+/// reference cycle.
+#[async_trait]
+pub trait Transport: Clone + Send + Sync + 'static {
+    type RecordsStream: Stream<Item = Vec<u8>> + Send + Unpin;
+
+    fn identity(&self) -> HelperIdentity;
+
+    async fn send<
+        D: Stream<Item = Vec<u8>> + Send + 'static,
+        Q: QueryIdBinding,
+        S: StepBinding,
+        R: RouteParams<RouteId, Q, S>,
+    >(
+        &self,
+        dest: HelperIdentity,
+        route: R,
+        data: D,
+    ) -> Result<(), TransportError>
+    where
+        Option<QueryId>: From<Q>,
+        Option<Step>: From<S>;
+
+    fn receive<R: RouteParams<NoResourceIdentifier, QueryId, Step>>(
+        &self,
+        from: HelperIdentity,
+        route: R,
+    ) -> Self::RecordsStream;
+}
+
+/// The payload of a `RouteId::Handshake` packet: what an initiating peer proposes when it first
+/// connects, before any `ReceiveQuery`/`Records`/`PrepareQuery` packet of theirs will be accepted
+/// by a transport that requires a handshake.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HandshakeRequest {
+    pub codec_version: u32,
+}
+
+/// What a [`TransportCallbacks::authenticate`] callback agrees to for a peer's session. Carried
+/// by whatever per-session table a given [`Transport`] backend keeps (e.g.
+/// `InMemoryTransport::sessions`) once its handshake completes.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HandshakeInfo {
+    pub session_id: String,
+    pub codec_version: u32,
+}
+
+/// The callbacks a [`Transport`] invokes for packets that aren't handled inline (`Records`
+/// streams are just `add_stream`; everything else needs the query-layer's attention). `T` is
+/// whatever reference to the transport itself the backend hands the callback (e.g.
+/// `Weak<InMemoryTransport>`), so a callback can call back into the transport it was invoked
+/// from.
+pub struct TransportCallbacks<'a, T> {
+    pub receive_query:
+        Box<dyn Fn(T, crate::helpers::query::QueryConfig) -> BoxFuture<'a, Result<QueryId, TransportError>> + Send + Sync>,
+    pub prepare_query:
+        Box<dyn Fn(T, crate::helpers::query::PrepareQuery) -> BoxFuture<'a, Result<(), TransportError>> + Send + Sync>,
+    /// Authenticate a peer's `RouteId::Handshake` request, returning the session info to record
+    /// for it or rejecting the handshake outright.
+    pub authenticate:
+        Box<dyn Fn(T, HandshakeRequest) -> BoxFuture<'a, Result<HandshakeInfo, TransportError>> + Send + Sync>,
+}
+
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+impl<'a, T> Default for TransportCallbacks<'a, T> {
+    fn default() -> Self {
+        Self {
+            receive_query: Box::new(|_, _| unimplemented!("receive_query callback not installed")),
+            prepare_query: Box::new(|_, _| unimplemented!("prepare_query callback not installed")),
+            authenticate: Box::new(|_, _| unimplemented!("authenticate callback not installed")),
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    future::Future,
+    num::NonZeroUsize,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use futures::Stream;
+
+/// Returned by a [`Send`] future whose index falls at or past one already passed to
+/// [`OrderingMpscSender::close`].
+#[derive(Debug, thiserror::Error)]
+#[error("{name}: index {index} sent after the stream was closed at {closed_at}")]
+pub struct SendAfterCloseError {
+    name: String,
+    index: usize,
+    closed_at: usize,
+}
+
+enum Slot<T> {
+    Empty,
+    Full(T),
+}
+
+struct Inner<T> {
+    name: String,
+    /// Size of the ring buffer backing `slots`, and the furthest a producer may run ahead of
+    /// `next_to_recv` before its `send` blocks.
+    window: usize,
+    slots: Vec<Slot<T>>,
+    /// The smallest index not yet handed to the receiver.
+    next_to_recv: usize,
+    /// Set once [`OrderingMpscSender::close`] runs; no index at or past this may be sent.
+    closed_at: Option<usize>,
+    /// Producers parked because their index doesn't yet fall in `[next_to_recv, next_to_recv +
+    /// window)`, woken individually as `next_to_recv` advances past them.
+    send_wakers: Vec<(usize, Waker)>,
+    /// The receiver's waker, parked when `next_to_recv`'s slot is still empty.
+    recv_waker: Option<Waker>,
+}
+
+impl<T> Inner<T> {
+    fn slot_mut(&mut self, index: usize) -> &mut Slot<T> {
+        let window = self.window;
+        &mut self.slots[index % window]
+    }
+
+    /// Wake every producer whose index is now inside the window, after `next_to_recv` advanced.
+    fn wake_unblocked_senders(&mut self) {
+        let limit = self.next_to_recv + self.window;
+        let (ready, pending) = std::mem::take(&mut self.send_wakers)
+            .into_iter()
+            .partition(|(index, _)| *index < limit);
+        self.send_wakers = pending;
+        for (_, waker) in ready {
+            waker.wake();
+        }
+    }
+}
+
+/// A bounded, index-ordered MPSC channel: producers submit `(index, value)` pairs that may
+/// arrive in any order, and the receiver observes them strictly by index, starting at `0`.
+///
+/// "Bounded" here caps *lookahead* rather than the total number of outstanding sends: a `send`
+/// for an index `window` or more past the smallest index the receiver hasn't yet drained is held
+/// `Pending` until that gap closes, the same way a slow consumer backpressures a bounded
+/// `futures::mpsc::channel`. Without this, a producer racing ahead of a single missing index
+/// would buffer an unbounded number of slots.
+#[must_use]
+pub fn ordering_mpsc<T, S: Into<String>>(
+    name: S,
+    window: NonZeroUsize,
+) -> (OrderingMpscSender<T>, OrderingMpscReceiver<T>) {
+    let window = window.get();
+    let inner = Arc::new(Mutex::new(Inner {
+        name: name.into(),
+        window,
+        slots: (0..window).map(|_| Slot::Empty).collect(),
+        next_to_recv: 0,
+        closed_at: None,
+        send_wakers: Vec::new(),
+        recv_waker: None,
+    }));
+    (
+        OrderingMpscSender {
+            inner: Arc::clone(&inner),
+        },
+        OrderingMpscReceiver { inner },
+    )
+}
+
+pub struct OrderingMpscSender<T> {
+    inner: Arc<Mutex<Inner<T>>>,
+}
+
+impl<T> Clone for OrderingMpscSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> OrderingMpscSender<T> {
+    /// Submit `value` for `index`. Resolves once `index` falls inside the channel's window (i.e.
+    /// the receiver has drained far enough), or with [`SendAfterCloseError`] if `index` falls at
+    /// or past an index already passed to [`Self::close`].
+    pub fn send(&self, index: usize, value: T) -> Send<'_, T> {
+        Send {
+            inner: &self.inner,
+            index,
+            value: Some(value),
+        }
+    }
+
+    /// Mark `index` as the end of the stream: every index below `index` must have been (or still
+    /// will be) sent, and none at or past it may be. Any `send` already blocked on a now-invalid
+    /// index is woken immediately with [`SendAfterCloseError`] rather than left hanging.
+    pub fn close(&self, index: usize) -> Close<'_, T> {
+        Close {
+            inner: &self.inner,
+            index,
+        }
+    }
+}
+
+pub struct Send<'a, T> {
+    inner: &'a Arc<Mutex<Inner<T>>>,
+    index: usize,
+    value: Option<T>,
+}
+
+impl<T> Future for Send<'_, T> {
+    type Output = Result<(), SendAfterCloseError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::get_mut(self);
+        let mut inner = this.inner.lock().unwrap();
+
+        if let Some(closed_at) = inner.closed_at {
+            if this.index >= closed_at {
+                return Poll::Ready(Err(SendAfterCloseError {
+                    name: inner.name.clone(),
+                    index: this.index,
+                    closed_at,
+                }));
+            }
+        }
+
+        if this.index >= inner.next_to_recv + inner.window {
+            inner.send_wakers.push((this.index, cx.waker().clone()));
+            return Poll::Pending;
+        }
+
+        let value = this.value.take().expect("Send polled after completion");
+        let next_to_recv = inner.next_to_recv;
+        *inner.slot_mut(this.index) = Slot::Full(value);
+        if this.index == next_to_recv {
+            if let Some(waker) = inner.recv_waker.take() {
+                waker.wake();
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+pub struct Close<'a, T> {
+    inner: &'a Arc<Mutex<Inner<T>>>,
+    index: usize,
+}
+
+impl<T> Future for Close<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::get_mut(self);
+        let mut inner = this.inner.lock().unwrap();
+        let closed_at = *inner.closed_at.get_or_insert(this.index);
+
+        let (stale, live) = std::mem::take(&mut inner.send_wakers)
+            .into_iter()
+            .partition(|(index, _)| *index >= closed_at);
+        inner.send_wakers = live;
+        for (_, waker) in stale {
+            waker.wake();
+        }
+
+        if this.index == inner.next_to_recv {
+            if let Some(waker) = inner.recv_waker.take() {
+                waker.wake();
+            }
+        }
+
+        Poll::Ready(())
+    }
+}
+
+pub struct OrderingMpscReceiver<T> {
+    inner: Arc<Mutex<Inner<T>>>,
+}
+
+impl<T> OrderingMpscReceiver<T> {
+    /// Consume this receiver as a plain [`Stream`], for async callers that don't want to name
+    /// [`OrderingMpscReceiver`] directly.
+    #[must_use]
+    pub fn into_stream(self) -> impl Stream<Item = T> {
+        self
+    }
+}
+
+impl<T> IntoIterator for OrderingMpscReceiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// A blocking iterator over this receiver's records in index order, for synchronous callers
+    /// (test harnesses, glue code) that live outside any async runtime. Yields `None` once the
+    /// channel is closed at its final index, mirroring `IntoIterator for mpsc::Receiver` in std.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { stream: self }
+    }
+}
+
+/// Blocking iterator returned by `OrderingMpscReceiver::into_iter`. Each [`Iterator::next`] call
+/// parks the current thread until the receiver's next index is ready (or the stream closes), so
+/// this must not be driven from inside an async runtime.
+pub struct IntoIter<T> {
+    stream: OrderingMpscReceiver<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        futures::executor::block_on(futures::StreamExt::next(&mut self.stream))
+    }
+}
+
+impl<T> Stream for OrderingMpscReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = Pin::get_mut(self);
+        let mut inner = this.inner.lock().unwrap();
+
+        let next = inner.next_to_recv;
+        match std::mem::replace(inner.slot_mut(next), Slot::Empty) {
+            Slot::Full(value) => {
+                inner.next_to_recv += 1;
+                inner.wake_unblocked_senders();
+                Poll::Ready(Some(value))
+            }
+            Slot::Empty => {
+                if inner.closed_at == Some(next) {
+                    return Poll::Ready(None);
+                }
+                inner.recv_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use futures::StreamExt;
+
+    use super::ordering_mpsc;
+
+    fn window(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[tokio::test]
+    async fn receives_in_index_order_regardless_of_send_order() {
+        let (tx, rx) = ordering_mpsc("test", window(4));
+        tx.send(2, "c").await.unwrap();
+        tx.send(0, "a").await.unwrap();
+        tx.send(1, "b").await.unwrap();
+        tx.close(3).await;
+
+        let received: Vec<_> = rx.collect().await;
+        assert_eq!(received, vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn send_blocks_until_the_window_advances() {
+        let (tx, mut rx) = ordering_mpsc("test", window(2));
+        // The window is [0, 2), so index 2 falls outside it and must block.
+        let mut send_2 = Box::pin(tx.send(2, "c"));
+        assert!(futures::poll!(&mut send_2).is_pending());
+
+        // Draining index 0 advances the window to [1, 3), which still excludes 2.
+        tx.send(0, "a").await.unwrap();
+        assert_eq!(rx.next().await, Some("a"));
+        assert!(futures::poll!(&mut send_2).is_pending());
+
+        // Draining index 1 advances the window to [2, 4), unblocking index 2.
+        tx.send(1, "b").await.unwrap();
+        assert_eq!(rx.next().await, Some("b"));
+        send_2.await.unwrap();
+        assert_eq!(rx.next().await, Some("c"));
+    }
+
+    #[tokio::test]
+    async fn close_ends_the_stream_at_the_closed_index() {
+        let (tx, rx) = ordering_mpsc::<&str, _>("test", window(4));
+        tx.send(0, "a").await.unwrap();
+        tx.close(1).await;
+
+        let received: Vec<_> = rx.collect().await;
+        assert_eq!(received, vec!["a"]);
+    }
+
+    #[tokio::test]
+    async fn send_at_or_past_the_closed_index_errors() {
+        let (tx, _rx) = ordering_mpsc::<&str, _>("test", window(4));
+        tx.close(1).await;
+
+        let err = tx.send(1, "late").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "test: index 1 sent after the stream was closed at 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn close_wakes_a_send_already_blocked_past_the_new_closed_index() {
+        let (tx, _rx) = ordering_mpsc::<&str, _>("test", window(1));
+        // The window is [0, 1), so a send at index 5 parks waiting for the window to advance.
+        let mut blocked = Box::pin(tx.send(5, "late"));
+        assert!(futures::poll!(&mut blocked).is_pending());
+
+        tx.close(2).await;
+
+        assert!(blocked.await.is_err());
+    }
+}
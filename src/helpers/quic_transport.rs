@@ -0,0 +1,503 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll, Waker},
+};
+
+use async_trait::async_trait;
+use futures::Stream;
+use quinn::{Connection, Endpoint, RecvStream, SendStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tracing::Instrument;
+
+use crate::{
+    helpers::{
+        HelperIdentity, NoResourceIdentifier, QueryIdBinding, RouteId, RouteParams, StepBinding,
+        Transport, TransportError,
+    },
+    protocol::{QueryId, Step},
+};
+
+/// Each record stream this helper sends or receives is keyed by the query it belongs to, the
+/// step within that query, and the peer it runs between — one QUIC unidirectional stream per
+/// key. Mirrors [`crate::test_fixture::network::InMemoryTransport`]'s `StreamKey`.
+type StreamKey = (QueryId, HelperIdentity, Step);
+
+/// A [`Transport`] backed by real QUIC connections (via `quinn`), one connection per peer
+/// [`HelperIdentity`]. Every `(QueryId, Step)` record stream is its own unidirectional QUIC
+/// stream rather than bytes multiplexed over a single shared pipe: that keeps delivery in order
+/// *within* a step (matching the ordering [`Transport::receive`] callers rely on) without a slow
+/// or stalled step blocking unrelated ones behind it on the connection, the same multiplexing
+/// media-over-QUIC uses to carry many logical tracks over one connection.
+///
+/// Connection setup (TLS handshake, certificate validation) is [`Endpoint`]'s job; this type only
+/// owns the peer table and the per-stream framing on top of it. Mapping an *inbound* connection
+/// to the [`HelperIdentity`] it belongs to isn't carried by QUIC/TLS itself here, so the first
+/// unidirectional stream a peer opens on a fresh connection is reserved as a hello frame
+/// containing its [`HelperIdentity`]; every later stream on that connection is a records stream.
+pub struct QuicTransport {
+    identity: HelperIdentity,
+    endpoint: Endpoint,
+    connections: Mutex<HashMap<HelperIdentity, Connection>>,
+    record_streams: RecordStreams,
+}
+
+impl QuicTransport {
+    /// Bind `endpoint` (already configured with this helper's server and client TLS config) as
+    /// `identity`, and start accepting inbound connections from peers. Use [`Self::connect_peer`]
+    /// to establish the outbound half for each other helper in the query.
+    #[must_use]
+    pub fn new(identity: HelperIdentity, endpoint: Endpoint) -> Arc<Self> {
+        let this = Arc::new(Self {
+            identity,
+            endpoint,
+            connections: Mutex::new(HashMap::new()),
+            record_streams: RecordStreams::default(),
+        });
+        this.clone().listen_for_peers();
+        this
+    }
+
+    /// Open (or replace) the outbound connection to `dest` at `addr`. Idempotent: safe to call
+    /// again after a peer restarts at a new address.
+    ///
+    /// Immediately opens the connection's hello stream and writes this helper's own
+    /// [`HelperIdentity`] to it, per the convention documented on [`QuicTransport`]: `dest`'s
+    /// [`Self::accept_peer_streams`] reads that hello frame before treating any later stream on
+    /// this connection as a records stream.
+    pub async fn connect_peer(
+        self: &Arc<Self>,
+        dest: HelperIdentity,
+        addr: SocketAddr,
+        server_name: &str,
+    ) -> Result<(), TransportError> {
+        let connecting = self.endpoint.connect(addr, server_name).map_err(io_err)?;
+        let connection = connecting.await.map_err(io_err)?;
+
+        let mut hello = connection.open_uni().await.map_err(io_err)?;
+        let identity = serde_json::to_vec(&self.identity).unwrap();
+        write_frame(&mut hello, &identity).await?;
+        hello.finish().map_err(io_err)?;
+
+        self.connections.lock().unwrap().insert(dest, connection);
+        Ok(())
+    }
+
+    fn connection(&self, dest: HelperIdentity) -> Result<Connection, TransportError> {
+        self.connections
+            .lock()
+            .unwrap()
+            .get(&dest)
+            .cloned()
+            .ok_or(TransportError::Rejected {
+                dest,
+                inner: format!("no QUIC connection to {dest:?}"),
+            })
+    }
+
+    /// Accept inbound connections for the lifetime of `endpoint`, spawning one task per
+    /// connection to read its hello frame and then fan its records streams into
+    /// `record_streams`.
+    fn listen_for_peers(self: Arc<Self>) {
+        tokio::spawn(
+            async move {
+                while let Some(connecting) = self.endpoint.accept() {
+                    let Ok(connection) = connecting.await else {
+                        continue;
+                    };
+                    let this = Arc::clone(&self);
+                    tokio::spawn(async move { this.accept_peer_streams(connection).await });
+                }
+            }
+            .instrument(tracing::info_span!("quic_listen", identity = ?self.identity)),
+        );
+    }
+
+    /// Read `connection`'s hello frame to learn the peer's [`HelperIdentity`], then spawn one
+    /// task per subsequent unidirectional stream to decode its header and forward its frames
+    /// into `record_streams`.
+    async fn accept_peer_streams(self: Arc<Self>, connection: Connection) {
+        let Ok(mut hello) = connection.accept_uni().await else {
+            return;
+        };
+        let Ok(from) = read_frame(&mut hello)
+            .await
+            .map(|bytes| serde_json::from_slice::<HelperIdentity>(&bytes))
+        else {
+            return;
+        };
+        let Ok(from) = from else { return };
+        self.connections
+            .lock()
+            .unwrap()
+            .entry(from)
+            .or_insert_with(|| connection.clone());
+
+        loop {
+            let Ok(recv) = connection.accept_uni().await else {
+                break;
+            };
+            let this = Arc::clone(&self);
+            tokio::spawn(async move { this.accept_records_stream(from, recv).await });
+        }
+    }
+
+    /// Decode `recv`'s header (the `QueryId`/`Step` it carries records for), then forward its
+    /// length-prefixed frames to whichever task is (or later will be) waiting on that
+    /// [`StreamKey`] via [`Transport::receive`].
+    async fn accept_records_stream(self: Arc<Self>, from: HelperIdentity, mut recv: RecvStream) {
+        let Ok(header) = read_frame(&mut recv).await else {
+            return;
+        };
+        let Ok((query_id, step)) = serde_json::from_slice::<(QueryId, Step)>(&header) else {
+            return;
+        };
+        let key = (query_id, from, step);
+        let (tx, rx) = unbounded_channel();
+        self.record_streams.deliver(key, rx);
+
+        loop {
+            match read_frame(&mut recv).await {
+                Ok(frame) if frame.is_empty() => break,
+                Ok(frame) => {
+                    if tx.send(frame).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    // `tx` is dropped here without sending the empty-frame marker, so `rx`'s
+                    // consumer sees the stream end exactly as it would on a clean close; trace
+                    // this case so an operator can still tell a crashed peer from one that
+                    // finished normally.
+                    tracing::warn!(
+                        from = ?from,
+                        key = ?key,
+                        error = %e,
+                        "records stream ended with an error, not a clean close"
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Write `frame` prefixed with its length as a little-endian `u32`, so the reader knows exactly
+/// how many bytes make up the next frame without relying on stream boundaries (QUIC streams
+/// don't preserve write-call boundaries). An empty frame is the end-of-stream marker.
+async fn write_frame(send: &mut SendStream, frame: &[u8]) -> Result<(), TransportError> {
+    let len = u32::try_from(frame.len()).unwrap();
+    send.write_all(&len.to_le_bytes()).await.map_err(io_err)?;
+    send.write_all(frame).await.map_err(io_err)
+}
+
+/// The inverse of [`write_frame`]. Returns an empty `Vec` once the peer sends the explicit
+/// empty-frame marker [`write_frame`] always writes before finishing its side of the stream.
+/// A genuine I/O failure partway through a frame (a dropped connection, a reset stream) is
+/// surfaced as a [`TransportError`] rather than folded into that same empty result — otherwise
+/// a peer that crashes mid-stream would look identical to one that finished cleanly, and
+/// [`QuicTransport::accept_records_stream`] would silently hand its caller a truncated record
+/// stream instead of an error.
+async fn read_frame(recv: &mut RecvStream) -> Result<Vec<u8>, TransportError> {
+    let mut len_buf = [0u8; 4];
+    recv.read_exact(&mut len_buf).await.map_err(io_err)?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+    let mut frame = vec![0u8; len];
+    recv.read_exact(&mut frame).await.map_err(io_err)?;
+    Ok(frame)
+}
+
+#[async_trait]
+impl Transport for Weak<QuicTransport> {
+    type RecordsStream = QuicRecordsStream;
+
+    fn identity(&self) -> HelperIdentity {
+        self.upgrade().unwrap().identity
+    }
+
+    async fn send<
+        D: Stream<Item = Vec<u8>> + Send + 'static,
+        Q: QueryIdBinding,
+        S: StepBinding,
+        R: RouteParams<RouteId, Q, S>,
+    >(
+        &self,
+        dest: HelperIdentity,
+        route: R,
+        data: D,
+    ) -> Result<(), TransportError>
+    where
+        Option<QueryId>: From<Q>,
+        Option<Step>: From<S>,
+    {
+        use futures::StreamExt;
+
+        let this = self.upgrade().unwrap();
+        debug_assert_eq!(
+            route.resource_identifier(),
+            RouteId::Records,
+            "QuicTransport only maps RouteId::Records onto per-step streams"
+        );
+        let query_id: Option<QueryId> = route.query_id().into();
+        let step: Option<Step> = route.step().into();
+        let query_id = query_id.expect("records route always carries a query id");
+        let step = step.expect("records route always carries a step");
+
+        let connection = this.connection(dest)?;
+        let mut send = connection.open_uni().await.map_err(io_err)?;
+
+        let header = serde_json::to_vec(&(query_id, step)).unwrap();
+        write_frame(&mut send, &header).await?;
+
+        let mut data = Box::pin(data);
+        while let Some(chunk) = data.next().await {
+            write_frame(&mut send, &chunk).await?;
+        }
+        // Empty frame marks the end of this step's stream, mirroring `close(idx)` on the
+        // in-memory/ordering_mpsc transports: the receiver's stream ends here rather than
+        // hanging waiting for a frame that will never come.
+        write_frame(&mut send, &[]).await?;
+        send.finish().map_err(io_err)
+    }
+
+    fn receive<R: RouteParams<NoResourceIdentifier, QueryId, Step>>(
+        &self,
+        from: HelperIdentity,
+        route: R,
+    ) -> Self::RecordsStream {
+        let this = self.upgrade().unwrap();
+        QuicRecordsStream::new(
+            (route.query_id(), from, route.step()),
+            this.record_streams.clone(),
+        )
+    }
+}
+
+enum RecvSlot {
+    /// `receive` asked for this key before its stream arrived; woken once [`RecordStreams::deliver`]
+    /// fills it in.
+    Waiting(Waker),
+    /// The stream's frames have started arriving; not yet claimed by a `receive` call.
+    Ready(UnboundedReceiver<Vec<u8>>),
+}
+
+/// Thread-safe table from [`StreamKey`] to its inbound frame channel, reconciling whichever of
+/// "the stream arrived" and "`receive` was called" happens first — the same ordering problem
+/// [`crate::test_fixture::network::InMemoryTransport`]'s `StreamCollection` solves for the
+/// in-memory transport.
+#[derive(Clone, Default)]
+struct RecordStreams {
+    slots: Arc<Mutex<HashMap<StreamKey, RecvSlot>>>,
+}
+
+impl RecordStreams {
+    fn deliver(&self, key: StreamKey, rx: UnboundedReceiver<Vec<u8>>) {
+        let mut slots = self.slots.lock().unwrap();
+        match slots.insert(key, RecvSlot::Ready(rx)) {
+            Some(RecvSlot::Waiting(waker)) => waker.wake(),
+            Some(RecvSlot::Ready(_)) => panic!("duplicate records stream for {key:?}"),
+            None => {}
+        }
+    }
+
+    fn poll_take(&self, key: &StreamKey, cx: &mut Context<'_>) -> Poll<UnboundedReceiver<Vec<u8>>> {
+        let mut slots = self.slots.lock().unwrap();
+        match slots.remove(key) {
+            Some(RecvSlot::Ready(rx)) => Poll::Ready(rx),
+            _ => {
+                slots.insert(key.clone(), RecvSlot::Waiting(cx.waker().clone()));
+                Poll::Pending
+            }
+        }
+    }
+}
+
+enum QuicRecordsStreamInner {
+    Pending(StreamKey, RecordStreams),
+    Ready(UnboundedReceiver<Vec<u8>>),
+}
+
+/// [`Transport::RecordsStream`] for [`QuicTransport`]: proxies the [`UnboundedReceiver`] fed by
+/// the QUIC stream matching this key, once it arrives.
+pub struct QuicRecordsStream {
+    inner: QuicRecordsStreamInner,
+}
+
+impl QuicRecordsStream {
+    fn new(key: StreamKey, streams: RecordStreams) -> Self {
+        Self {
+            inner: QuicRecordsStreamInner::Pending(key, streams),
+        }
+    }
+}
+
+impl Stream for QuicRecordsStream {
+    type Item = Vec<u8>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = Pin::get_mut(self);
+        loop {
+            match &mut this.inner {
+                QuicRecordsStreamInner::Pending(key, streams) => match streams.poll_take(key, cx) {
+                    Poll::Ready(rx) => this.inner = QuicRecordsStreamInner::Ready(rx),
+                    Poll::Pending => return Poll::Pending,
+                },
+                QuicRecordsStreamInner::Ready(rx) => return rx.poll_recv(cx),
+            }
+        }
+    }
+}
+
+fn io_err(e: impl std::fmt::Display) -> TransportError {
+    TransportError::Io {
+        inner: io::Error::new(io::ErrorKind::Other, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::StreamExt;
+    use quinn::{ClientConfig, Endpoint, ServerConfig};
+
+    use super::{read_frame, write_frame, QuicTransport};
+    use crate::{
+        helpers::{HelperIdentity, RouteId, Transport},
+        protocol::{QueryId, Step},
+    };
+
+    const STEP: &str = "quic-transport-test";
+
+    /// A self-signed endpoint bound to an ephemeral loopback port, able to act as both a `quinn`
+    /// client and server. Returns the endpoint and its certificate, so a test can later tell it
+    /// which peer certificates to trust via [`trust_peer`].
+    fn self_signed_endpoint() -> (Endpoint, Vec<u8>) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_der = cert.serialize_der().unwrap();
+        let priv_key = rustls::PrivateKey(cert.serialize_private_key_der());
+        let cert_chain = vec![rustls::Certificate(cert_der.clone())];
+
+        let server_config = ServerConfig::with_single_cert(cert_chain, priv_key).unwrap();
+        let endpoint = Endpoint::server(server_config, "127.0.0.1:0".parse().unwrap()).unwrap();
+
+        (endpoint, cert_der)
+    }
+
+    /// Point `endpoint`'s client config at a root store trusting only `peer_cert_der`, without
+    /// standing up a CA.
+    fn trust_peer(endpoint: &mut Endpoint, peer_cert_der: &[u8]) {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(peer_cert_der.to_vec())).unwrap();
+        let client_crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        endpoint.set_default_client_config(ClientConfig::new(Arc::new(client_crypto)));
+    }
+
+    /// A loopback QUIC client/server pair behind a throwaway self-signed certificate. The only
+    /// thing under test here is frame-level stream handling, not TLS trust, so the client skips
+    /// certificate verification rather than standing up a CA.
+    async fn loopback_pair() -> (Endpoint, quinn::Connection, quinn::Connection) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_der = cert.serialize_der().unwrap();
+        let priv_key = rustls::PrivateKey(cert.serialize_private_key_der());
+        let cert_chain = vec![rustls::Certificate(cert_der.clone())];
+
+        let server_config = ServerConfig::with_single_cert(cert_chain, priv_key).unwrap();
+        let server = Endpoint::server(server_config, "127.0.0.1:0".parse().unwrap()).unwrap();
+        let server_addr = server.local_addr().unwrap();
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(cert_der)).unwrap();
+        let client_crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let mut client = Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+        client.set_default_client_config(ClientConfig::new(Arc::new(client_crypto)));
+
+        let client_conn = client.connect(server_addr, "localhost").unwrap().await.unwrap();
+        let server_conn = server.accept().await.unwrap().await.unwrap();
+
+        (client, client_conn, server_conn)
+    }
+
+    #[tokio::test]
+    async fn read_frame_surfaces_error_on_reset_mid_frame_instead_of_silent_eof() {
+        let (_client, client_conn, server_conn) = loopback_pair().await;
+
+        let mut send = client_conn.open_uni().await.unwrap();
+        // Write only the first 2 of the 4 length-prefix bytes, then reset the stream instead of
+        // finishing it cleanly: the receiver is left mid-frame with no way to tell "more is
+        // coming" from "the peer is gone" except by the read failing.
+        send.write_all(&1u32.to_le_bytes()[..2]).await.unwrap();
+        send.reset(0u32.into()).unwrap();
+
+        let mut recv = server_conn.accept_uni().await.unwrap();
+        let result = read_frame(&mut recv).await;
+        assert!(
+            result.is_err(),
+            "a stream reset mid-frame must surface as an error, not the clean-close empty frame"
+        );
+    }
+
+    #[tokio::test]
+    async fn read_frame_returns_empty_vec_on_explicit_empty_frame_marker() {
+        let (_client, client_conn, server_conn) = loopback_pair().await;
+
+        let mut send = client_conn.open_uni().await.unwrap();
+        write_frame(&mut send, &[]).await.unwrap();
+        send.finish().unwrap();
+
+        let mut recv = server_conn.accept_uni().await.unwrap();
+        let frame = read_frame(&mut recv).await.unwrap();
+        assert!(frame.is_empty());
+    }
+
+    #[tokio::test]
+    async fn connect_send_receive_delivers_records_end_to_end() {
+        let (mut endpoint1, cert1) = self_signed_endpoint();
+        let (mut endpoint2, cert2) = self_signed_endpoint();
+        trust_peer(&mut endpoint1, &cert2);
+        trust_peer(&mut endpoint2, &cert1);
+        let addr1 = endpoint1.local_addr().unwrap();
+        let addr2 = endpoint2.local_addr().unwrap();
+
+        let transport1 = QuicTransport::new(HelperIdentity::ONE, endpoint1);
+        let transport2 = QuicTransport::new(HelperIdentity::TWO, endpoint2);
+
+        transport1
+            .connect_peer(HelperIdentity::TWO, addr2, "localhost")
+            .await
+            .unwrap();
+        transport2
+            .connect_peer(HelperIdentity::ONE, addr1, "localhost")
+            .await
+            .unwrap();
+
+        let transport1 = Arc::downgrade(&transport1);
+        let transport2 = Arc::downgrade(&transport2);
+
+        let mut recv = transport2.receive(HelperIdentity::ONE, (QueryId, Step::from(STEP)));
+        transport1
+            .send(
+                HelperIdentity::TWO,
+                (RouteId::Records, QueryId, Step::from(STEP)),
+                futures::stream::iter(vec![vec![1, 2, 3]]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Some(vec![1, 2, 3]),
+            recv.next().await,
+            "a record sent after connect_peer on both sides must actually be identified and \
+             delivered, not silently dropped for lack of a hello frame"
+        );
+    }
+}
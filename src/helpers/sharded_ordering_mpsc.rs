@@ -0,0 +1,160 @@
+use std::{
+    num::NonZeroUsize,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+
+use crate::helpers::ordering_mpsc::{ordering_mpsc, OrderingMpscReceiver, OrderingMpscSender};
+
+pub use crate::helpers::ordering_mpsc::SendAfterCloseError;
+
+/// Create a sharded, index-ordered channel: a single logical stream of `(index, value)` pairs
+/// fanned out across `shards` independent [`ordering_mpsc`] channels keyed by `index % shards`,
+/// so a high-throughput step isn't bottlenecked on one channel's window and one task draining
+/// it. Mirrors how a Kafka topic's partitions let producers parallelize while a consumer still
+/// sees each partition in order; here the partitions are merged back into the original global
+/// order on the receiving side rather than left for the caller to reassemble.
+///
+/// Each shard keeps its own `window`-sized lookahead exactly as a plain [`ordering_mpsc`] would,
+/// so the effective total buffering scales with `shards`.
+#[must_use]
+pub fn sharded_ordering_mpsc<T, S: Into<String>>(
+    name: S,
+    shards: NonZeroUsize,
+    window: NonZeroUsize,
+) -> (ShardedOrderingSender<T>, ShardedOrderingReceiver<T>) {
+    let name = name.into();
+    let shard_count = shards.get();
+    let mut senders = Vec::with_capacity(shard_count);
+    let mut receivers = Vec::with_capacity(shard_count);
+    for shard in 0..shard_count {
+        let (tx, rx) = ordering_mpsc(format!("{name}-shard{shard}"), window);
+        senders.push(tx);
+        receivers.push(rx);
+    }
+    (
+        ShardedOrderingSender { shards: senders },
+        ShardedOrderingReceiver {
+            shards: receivers,
+            next: 0,
+        },
+    )
+}
+
+/// For a stream closed at global index `index`, the local index at which `shard` (of
+/// `shard_count` total, round-robin over `index % shard_count`) must itself be closed: the count
+/// of global indices below `index` that landed on `shard`.
+fn local_closed_at(index: usize, shard_count: usize, shard: usize) -> usize {
+    (index + shard_count - 1 - shard) / shard_count
+}
+
+pub struct ShardedOrderingSender<T> {
+    shards: Vec<OrderingMpscSender<T>>,
+}
+
+impl<T> Clone for ShardedOrderingSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: self.shards.clone(),
+        }
+    }
+}
+
+impl<T> ShardedOrderingSender<T> {
+    /// Submit `value` for global `index`, routed to shard `index % shard_count` at local index
+    /// `index / shard_count`. Backpressures exactly like [`OrderingMpscSender::send`], scoped to
+    /// that one shard.
+    pub async fn send(&self, index: usize, value: T) -> Result<(), SendAfterCloseError> {
+        let shard_count = self.shards.len();
+        let shard = index % shard_count;
+        let local_index = index / shard_count;
+        self.shards[shard].send(local_index, value).await
+    }
+
+    /// Close the stream at global `index`, translating it to each shard's own local closing
+    /// index so the merged receiver terminates at exactly `index` only once every shard has
+    /// closed, rather than as soon as the first one does.
+    pub async fn close(&self, index: usize) {
+        let shard_count = self.shards.len();
+        for (shard, sender) in self.shards.iter().enumerate() {
+            sender
+                .close(local_closed_at(index, shard_count, shard))
+                .await;
+        }
+    }
+}
+
+pub struct ShardedOrderingReceiver<T> {
+    shards: Vec<OrderingMpscReceiver<T>>,
+    /// The next global index to emit. `shards[next % shards.len()]` is always the one shard the
+    /// merge is currently waiting on; since each shard already yields its own items in order,
+    /// merging needs nothing more than remembering whose turn it is next — no separate reorder
+    /// buffer keyed by global index is needed on top of what `ordering_mpsc` already does per
+    /// shard.
+    next: usize,
+}
+
+impl<T> Stream for ShardedOrderingReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = Pin::get_mut(self);
+        let shard = this.next % this.shards.len();
+        match Pin::new(&mut this.shards[shard]).poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                this.next += 1;
+                Poll::Ready(Some(value))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use futures::StreamExt;
+
+    use super::sharded_ordering_mpsc;
+
+    fn n(v: usize) -> NonZeroUsize {
+        NonZeroUsize::new(v).unwrap()
+    }
+
+    #[tokio::test]
+    async fn merges_shards_back_into_global_order() {
+        let (tx, rx) = sharded_ordering_mpsc("test", n(3), n(4));
+        // Global indices route to shard `index % 3`; send scrambled both within and across shards.
+        for (index, value) in [(3, "d"), (0, "a"), (4, "e"), (1, "b"), (2, "c"), (5, "f")] {
+            tx.send(index, value).await.unwrap();
+        }
+        tx.close(6).await;
+
+        let received: Vec<_> = rx.collect().await;
+        assert_eq!(received, vec!["a", "b", "c", "d", "e", "f"]);
+    }
+
+    #[tokio::test]
+    async fn close_only_ends_the_merged_stream_once_every_shard_has_closed() {
+        let (tx, rx) = sharded_ordering_mpsc("test", n(3), n(4));
+        for index in 0..7 {
+            tx.send(index, index).await.unwrap();
+        }
+        tx.close(7).await;
+
+        let received: Vec<_> = rx.collect().await;
+        assert_eq!(received, (0..7).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn send_at_or_past_the_closed_index_errors() {
+        let (tx, _rx) = sharded_ordering_mpsc::<&str, _>("test", n(3), n(4));
+        tx.close(2).await;
+
+        assert!(tx.send(2, "late").await.is_err());
+    }
+}
@@ -7,6 +7,8 @@ use crate::secret_sharing::replicated::malicious::{
 };
 use crate::secret_sharing::{ArithmeticSecretSharing, SecretSharing};
 use async_trait::async_trait;
+use futures::future::try_join_all;
+use std::iter::zip;
 use std::marker::PhantomData;
 
 #[derive(Debug)]
@@ -109,6 +111,102 @@ where
     }))
 }
 
+/// The probability that a single [`generate_random_bits`](Context::generate_random_bits) candidate
+/// survives [`is_less_than_p`], i.e. `p / 2^l` where `l` is the number of bits
+/// `generate_random_bits` draws (the smallest `l` with `2^l >= p`). ≈0.97 for `Fp31`'s `p = 31`
+/// (`l = 5`), ≈1 for `Fp32BitPrime`'s `p = 2^32 - 5` (`l = 32`).
+fn p_survive<F: Field>() -> f64 {
+    let l = u128::BITS - (F::PRIME - 1).leading_zeros();
+    F::PRIME as f64 / (1u128 << l) as f64
+}
+
+/// How many candidates [`solved_bits_batch`] must draw so that, modeling survivors as
+/// `Binomial(draws, p_survive)`, at least `count` survive with probability at least `1 -
+/// shortfall_bound`.
+///
+/// Chernoff's lower-tail bound gives `Pr[X < (1 - δ)μ] <= exp(-μδ²/2)` for `μ = draws·p_survive`.
+/// Setting `(1 - δ)μ = count` and `exp(-μδ²/2) = shortfall_bound` and solving the resulting
+/// quadratic in `sqrt(μ)` for `μ` yields the formula below; `draws` is then `μ / p_survive`.
+fn draws_for(count: usize, p_survive: f64, shortfall_bound: f64) -> usize {
+    let count = count as f64;
+    let a = (2.0 * (1.0 / shortfall_bound).ln()).sqrt();
+    let sqrt_mu = (a + (a * a + 4.0 * count).sqrt()) / 2.0;
+    let mu = sqrt_mu * sqrt_mu;
+    (mu / p_survive).ceil() as usize
+}
+
+/// Generates `count` [`RandomBitsShare`]s, amortizing [`solved_bits`]'s sequential abort-and-retry
+/// pattern into a handful of batched rounds.
+///
+/// Draws [`draws_for`] candidate bit-vectors (enough to survive the ~3% `Fp31`/~0 `Fp32BitPrime`
+/// abort rate and still clear `count` with probability `1 - shortfall_bound`) via
+/// [`generate_random_bits`](Context::generate_random_bits) in parallel, runs
+/// [`is_less_than_p`] for every candidate in one batched round (one [`RecordId`] per candidate),
+/// and keeps the survivors — the same publicly revealed comparison bit every helper already
+/// agrees to abort or keep on in [`solved_bits`], so all three helpers still end up with the same
+/// kept candidate set. If the draw falls short of `count` (only possible if this run did worse
+/// than `shortfall_bound` predicted), the remainder is filled by recursing once on a narrowed
+/// context.
+pub async fn solved_bits_batch<F, S, C>(
+    ctx: C,
+    count: usize,
+    shortfall_bound: f64,
+) -> Result<Vec<RandomBitsShare<F, S>>, Error>
+where
+    F: Field,
+    S: ArithmeticSecretSharing<F>,
+    C: Context<F, Share = S> + std::marker::Send,
+{
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let draws = draws_for(count, p_survive::<F>(), shortfall_bound);
+
+    let b_bs: Vec<Vec<S>> = try_join_all((0..draws).map(|i| {
+        let ctx = ctx.narrow(&Step::RandomBits);
+        async move { ctx.generate_random_bits(RecordId::from(i)).await }
+    }))
+    .await?;
+
+    let survived: Vec<bool> = try_join_all(b_bs.iter().enumerate().map(|(i, b_b)| {
+        let ctx = ctx.clone();
+        async move { is_less_than_p(ctx, RecordId::from(i), b_b).await }
+    }))
+    .await?;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut shares: Vec<RandomBitsShare<F, S>> = zip(b_bs, survived)
+        .filter_map(|(b_b, kept)| kept.then_some(b_b))
+        .map(|b_b| {
+            let b_p: S = b_b
+                .iter()
+                .enumerate()
+                .fold(S::ZERO, |acc, (i, x)| acc + &(x.clone() * F::from(1 << i)));
+            RandomBitsShare {
+                b_b,
+                b_p,
+                _marker: PhantomData::default(),
+            }
+        })
+        .collect();
+
+    if shares.len() > count {
+        shares.truncate(count);
+    } else if shares.len() < count {
+        let remaining = count - shares.len();
+        let more = Box::pin(solved_bits_batch(
+            ctx.narrow(&Step::Shortfall),
+            remaining,
+            shortfall_bound,
+        ))
+        .await?;
+        shares.extend(more);
+    }
+
+    Ok(shares)
+}
+
 async fn is_less_than_p<F, C, S>(ctx: C, record_id: RecordId, b_b: &[S]) -> Result<bool, Error>
 where
     F: Field,
@@ -129,6 +227,7 @@ enum Step {
     RandomBits,
     IsPLessThanB,
     RevealC,
+    Shortfall,
 }
 
 impl crate::protocol::Substep for Step {}
@@ -139,6 +238,7 @@ impl AsRef<str> for Step {
             Self::RandomBits => "random_bits",
             Self::IsPLessThanB => "is_p_less_than_b",
             Self::RevealC => "reveal_c",
+            Self::Shortfall => "shortfall",
         }
     }
 }
@@ -252,6 +352,46 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    pub async fn batch_fp31() -> Result<(), Error> {
+        use crate::protocol::boolean::solved_bits::solved_bits_batch;
+
+        let world = TestWorld::new().await;
+        let ctx = world.contexts::<Fp31>();
+        let [c0, c1, c2] = ctx;
+        let count = 20;
+
+        let [s0, s1, s2] = join3(
+            solved_bits_batch(c0, count, 1e-9),
+            solved_bits_batch(c1, count, 1e-9),
+            solved_bits_batch(c2, count, 1e-9),
+        )
+        .await;
+        let (s0, s1, s2) = (s0?, s1?, s2?);
+
+        assert_eq!(s0.len(), count);
+        assert_eq!(s1.len(), count);
+        assert_eq!(s2.len(), count);
+
+        for ((r0, r1), r2) in zip(zip(s0, s1), s2) {
+            assert_eq!(r0.b_b.len(), r1.b_b.len());
+            assert_eq!(r1.b_b.len(), r2.b_b.len());
+
+            let b_b = (0..r0.b_b.len())
+                .map(|i| {
+                    let bit = (&r0.b_b[i], &r1.b_b[i], &r2.b_b[i]).reconstruct();
+                    assert!(bit == Fp31::ZERO || bit == Fp31::ONE);
+                    bit
+                })
+                .collect::<Vec<_>>();
+            let b_p = (&r0.b_p, &r1.b_p, &r2.b_p).reconstruct();
+
+            assert_eq!(b_p.as_u128(), bits_to_value(&b_b));
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     pub async fn malicious() {
         let world = TestWorld::new().await;
@@ -0,0 +1,388 @@
+use super::bitwise_less_than_prime::BitwiseLessThanPrime;
+use super::solved_bits::solved_bits;
+use crate::error::Error;
+use crate::ff::Field;
+use crate::protocol::{context::Context, RecordId};
+use crate::secret_sharing::ArithmeticSecretSharing;
+
+/// Checks that a decrypted trigger-value contribution `[a]` lies in the legal `[0, 2^b)` range,
+/// without ever revealing `a` itself, so a malicious report collector can't secret-share an
+/// out-of-range value to silently corrupt aggregation. Returns a secret-shared `1`/`0` validity
+/// flag the aggregation pipeline multiplies contributions by, zeroing out invalid ones.
+///
+/// Follows the bit-decomposition technique from the same Damgård et al. paper [`solved_bits`]
+/// already implements "RAN_2" from: draw a random solved-bits mask `[r]` (bit length `l >= b`, so
+/// the mask's magnitude doesn't leak anything about `b`-bit-range membership), reveal `c = a + r`
+/// in the clear, recover `[a]`'s bits from the public `c` and the secret bits `[r]_B` via a
+/// bitwise circuit, then run [`BitwiseLessThanPrime::less_than_constant`] on the recovered bits
+/// against the constant `2^b`.
+///
+/// `c = a + r` is computed mod the field's prime `p`, so it may have wrapped past `p` even though
+/// `a` and `r` individually didn't; the subtract-then-correct circuit below accounts for that (see
+/// [`recover_bits`]).
+pub async fn validate_trigger_value<F, S, C>(
+    ctx: C,
+    record_id: RecordId,
+    a: &S,
+    b: u32,
+) -> Result<S, Error>
+where
+    F: Field,
+    S: ArithmeticSecretSharing<F>,
+    C: Context<F, Share = S> + std::marker::Send,
+{
+    let mask = {
+        let mut attempt = 0_u32;
+        loop {
+            let ctx = ctx
+                .narrow(&Step::GenerateMask)
+                .narrow(&AttemptStep::new(attempt));
+            if let Some(mask) = solved_bits(ctx, record_id).await? {
+                break mask;
+            }
+            attempt += 1;
+        }
+    };
+    let l = mask.b_b.len();
+    debug_assert!(
+        l >= b as usize,
+        "mask must carry at least as many bits as the range check, or its magnitude would leak `b`"
+    );
+
+    let sum_share = a.clone() + &mask.b_p;
+    let c = ctx
+        .narrow(&Step::RevealSum)
+        .reveal(record_id, &sum_share)
+        .await?;
+
+    let a_bits = recover_bits(
+        ctx.narrow(&Step::Recompose),
+        record_id,
+        c.as_u128(),
+        &mask.b_b,
+    )
+    .await?;
+
+    BitwiseLessThanPrime::less_than_constant(
+        ctx.narrow(&Step::RangeCheck),
+        record_id,
+        &a_bits,
+        1_u128 << b,
+    )
+    .await
+}
+
+/// Recovers `[a]_B`, the bitwise sharing of `a = c - r mod p`, from the publicly revealed `c` and
+/// the secret bits `[r]_B`, in two ripple passes over `r`'s `l` bits:
+///
+/// 1. A subtractor computes `diff = c - r` bit by bit treating `c` as a public constant, along
+///    with its final borrow-out bit. `borrow_out` is `1` exactly when `c < r` as integers, which
+///    is exactly the case where `a + r` wrapped past `p` when `c` was formed (since `a`, `r` are
+///    both in `[0, p)`, `c < r` can only happen if `c = a + r - p`).
+/// 2. If the subtraction wrapped (`c < r`), `a = diff + p`; otherwise `a = diff` unchanged. Rather
+///    than branching on the secret `borrow_out`, every bit of `p` is conditionally zeroed by
+///    `borrow_out` (a local per-bit scalar select, since each bit of `p` is a public constant) and
+///    added into `diff` with a second ripple adder, discarding the final carry — a no-op addition
+///    of all zero bits when `borrow_out` is `0`.
+async fn recover_bits<F, S, C>(
+    ctx: C,
+    record_id: RecordId,
+    c: u128,
+    r_bits: &[S],
+) -> Result<Vec<S>, Error>
+where
+    F: Field,
+    S: ArithmeticSecretSharing<F>,
+    C: Context<F, Share = S> + std::marker::Send,
+{
+    let l = r_bits.len();
+    let one = ctx.share_known_value(F::ONE);
+
+    let mut diff_bits = Vec::with_capacity(l);
+    let mut borrow = ctx.share_known_value(F::ZERO);
+    for (i, r_i) in r_bits.iter().enumerate() {
+        let c_i = (c >> i) & 1 == 1;
+        let (diff_i, borrow_out) = sub_bit(
+            ctx.narrow(&Step::Subtract).narrow(&BitOpStep(i)),
+            record_id,
+            &one,
+            c_i,
+            r_i,
+            &borrow,
+        )
+        .await?;
+        diff_bits.push(diff_i);
+        borrow = borrow_out;
+    }
+    let wrapped = borrow;
+
+    let mut a_bits = Vec::with_capacity(l);
+    let mut carry = ctx.share_known_value(F::ZERO);
+    for (i, diff_i) in diff_bits.iter().enumerate() {
+        // `p_i * wrapped`: zero unless this bit of `p` is set, in which case it's `wrapped`
+        // itself. `p_i` is a public constant, so this is a local scalar select, not a multiply.
+        let p_i = (PRIME::<F>() >> i) & 1 == 1;
+        let addend_i = if p_i {
+            wrapped.clone()
+        } else {
+            ctx.share_known_value(F::ZERO)
+        };
+        let (sum_i, carry_out) = add_bit(
+            ctx.narrow(&Step::CorrectWrap).narrow(&BitOpStep(i)),
+            record_id,
+            diff_i,
+            &addend_i,
+            &carry,
+        )
+        .await?;
+        a_bits.push(sum_i);
+        carry = carry_out;
+    }
+
+    Ok(a_bits)
+}
+
+#[allow(non_snake_case)]
+fn PRIME<F: Field>() -> u128 {
+    F::PRIME
+}
+
+/// One ripple step of a subtractor with a public minuend bit: `(diff_i, borrow_out) = c_i - r_i -
+/// borrow_in`, expressed as XOR/AND gates so every operation on two secret values goes through
+/// [`Context::multiply`] and every operation involving only a public constant is local.
+async fn sub_bit<F, S, C>(
+    ctx: C,
+    record_id: RecordId,
+    one: &S,
+    c_i: bool,
+    r_i: &S,
+    borrow_in: &S,
+) -> Result<(S, S), Error>
+where
+    F: Field,
+    S: ArithmeticSecretSharing<F>,
+    C: Context<F, Share = S> + std::marker::Send,
+{
+    // XOR(c_i, r_i): `r_i` unchanged if `c_i` is 0, `1 - r_i` if `c_i` is 1. Local either way,
+    // since `c_i` is a public constant.
+    let xor_c_r = if c_i { one.clone() - r_i } else { r_i.clone() };
+    let diff_i = xor_secret(ctx.narrow(&Step::Diff), record_id, &xor_c_r, borrow_in).await?;
+
+    // NOT(c_i) AND r_i: `r_i` if `c_i` is 0, `0` if `c_i` is 1. Local.
+    let not_c_and_r = if c_i { r_i.clone() - r_i } else { r_i.clone() };
+    let not_xor_c_r = one.clone() - &xor_c_r;
+    let term2 = ctx
+        .narrow(&Step::BorrowTerm)
+        .multiply(record_id, &not_xor_c_r, borrow_in)
+        .await?;
+    let borrow_out = or(ctx.narrow(&Step::Borrow), record_id, &not_c_and_r, &term2).await?;
+
+    Ok((diff_i, borrow_out))
+}
+
+/// One ripple step of an ordinary secret+secret full adder: `(sum_i, carry_out) = x_i + y_i +
+/// carry_in`.
+async fn add_bit<F, S, C>(
+    ctx: C,
+    record_id: RecordId,
+    x_i: &S,
+    y_i: &S,
+    carry_in: &S,
+) -> Result<(S, S), Error>
+where
+    F: Field,
+    S: ArithmeticSecretSharing<F>,
+    C: Context<F, Share = S> + std::marker::Send,
+{
+    let xor_xy = xor_secret(ctx.narrow(&Step::Diff), record_id, x_i, y_i).await?;
+    let sum_i = xor_secret(ctx.narrow(&Step::Sum), record_id, &xor_xy, carry_in).await?;
+
+    let and_xy = ctx
+        .narrow(&Step::AndXY)
+        .multiply(record_id, x_i, y_i)
+        .await?;
+    let and_xor_carry = ctx
+        .narrow(&Step::AndXorCarry)
+        .multiply(record_id, &xor_xy, carry_in)
+        .await?;
+    let carry_out = or(ctx.narrow(&Step::Carry), record_id, &and_xy, &and_xor_carry).await?;
+
+    Ok((sum_i, carry_out))
+}
+
+/// `x XOR y` for two secret bits: `x + y - 2xy`.
+async fn xor_secret<F, S, C>(ctx: C, record_id: RecordId, x: &S, y: &S) -> Result<S, Error>
+where
+    F: Field,
+    S: ArithmeticSecretSharing<F>,
+    C: Context<F, Share = S> + std::marker::Send,
+{
+    let xy = ctx.multiply(record_id, x, y).await?;
+    Ok(x.clone() + y - &(xy * F::from(2)))
+}
+
+/// `x OR y = x + y - xy`, for two secret bits.
+async fn or<F, S, C>(ctx: C, record_id: RecordId, x: &S, y: &S) -> Result<S, Error>
+where
+    F: Field,
+    S: ArithmeticSecretSharing<F>,
+    C: Context<F, Share = S> + std::marker::Send,
+{
+    let xy = ctx.multiply(record_id, x, y).await?;
+    Ok(x.clone() + y - &xy)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Step {
+    GenerateMask,
+    RevealSum,
+    Recompose,
+    Subtract,
+    CorrectWrap,
+    RangeCheck,
+    Diff,
+    Sum,
+    BorrowTerm,
+    Borrow,
+    AndXY,
+    AndXorCarry,
+    Carry,
+}
+
+impl crate::protocol::Substep for Step {}
+
+impl AsRef<str> for Step {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::GenerateMask => "generate_mask",
+            Self::RevealSum => "reveal_sum",
+            Self::Recompose => "recompose",
+            Self::Subtract => "subtract",
+            Self::CorrectWrap => "correct_wrap",
+            Self::RangeCheck => "range_check",
+            Self::Diff => "diff",
+            Self::Sum => "sum",
+            Self::BorrowTerm => "borrow_term",
+            Self::Borrow => "borrow",
+            Self::AndXY => "and_xy",
+            Self::AndXorCarry => "and_xor_carry",
+            Self::Carry => "carry",
+        }
+    }
+}
+
+/// Distinguishes the `i`th bit's gates from every other bit's within the same ripple pass, since
+/// each bit position needs its own independent [`Context::multiply`] calls even though they all
+/// share the same [`RecordId`]. Bounded to 64 entries because `i` only ever ranges over a mask's
+/// bit length, which is well under that for every field this protocol supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BitOpStep(usize);
+
+impl crate::protocol::Substep for BitOpStep {}
+
+impl AsRef<str> for BitOpStep {
+    fn as_ref(&self) -> &str {
+        const NAMES: [&str; 64] = [
+            "bit0", "bit1", "bit2", "bit3", "bit4", "bit5", "bit6", "bit7", "bit8", "bit9",
+            "bit10", "bit11", "bit12", "bit13", "bit14", "bit15", "bit16", "bit17", "bit18",
+            "bit19", "bit20", "bit21", "bit22", "bit23", "bit24", "bit25", "bit26", "bit27",
+            "bit28", "bit29", "bit30", "bit31", "bit32", "bit33", "bit34", "bit35", "bit36",
+            "bit37", "bit38", "bit39", "bit40", "bit41", "bit42", "bit43", "bit44", "bit45",
+            "bit46", "bit47", "bit48", "bit49", "bit50", "bit51", "bit52", "bit53", "bit54",
+            "bit55", "bit56", "bit57", "bit58", "bit59", "bit60", "bit61", "bit62", "bit63",
+        ];
+        NAMES[self.0]
+    }
+}
+
+/// Distinguishes each retry of the mask-generation loop above from every other retry, so a
+/// `solved_bits` abort gets a freshly narrowed context (and thus fresh PRSS randomness) on the
+/// next attempt rather than retrying with the same narrowed context and looping forever. Unlike
+/// [`BitOpStep`], the number of retries has no a priori bound (an abort on every single attempt is
+/// vanishingly unlikely but not impossible), so this builds its name instead of indexing a fixed
+/// table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct AttemptStep(String);
+
+impl AttemptStep {
+    fn new(attempt: u32) -> Self {
+        Self(format!("attempt{attempt}"))
+    }
+}
+
+impl crate::protocol::Substep for AttemptStep {}
+
+impl AsRef<str> for AttemptStep {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(all(test, not(feature = "shuttle")))]
+mod tests {
+    use super::validate_trigger_value;
+    use crate::ff::{Field, Fp31};
+    use crate::protocol::RecordId;
+    use crate::secret_sharing::SharedValue;
+    use crate::test_fixture::{Reconstruct, TestWorld};
+
+    /// Secret-shares `a`, runs [`validate_trigger_value`] against range `[0, 2^b)`, and
+    /// reconstructs the resulting validity flag.
+    async fn validate(a: u128, b: u32) -> Fp31 {
+        let world = TestWorld::new().await;
+        let [r0, r1, r2] = world
+            .semi_honest(Fp31::from(a), |ctx, a_share| async move {
+                validate_trigger_value(ctx, RecordId::from(0), &a_share, b)
+                    .await
+                    .unwrap()
+            })
+            .await;
+        (r0, r1, r2).reconstruct()
+    }
+
+    #[tokio::test]
+    async fn accepts_values_inside_the_range() {
+        for a in [0_u128, 1, 6, 7] {
+            assert_eq!(
+                validate(a, 3).await,
+                Fp31::ONE,
+                "{a} should be valid for b=3"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_values_at_or_past_the_range_boundary() {
+        for a in [8_u128, 9, 15, 30] {
+            assert_eq!(
+                validate(a, 3).await,
+                Fp31::ZERO,
+                "{a} should be invalid for b=3"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn handles_the_range_boundary_for_every_bit_width_the_field_allows() {
+        // b ranges from "nothing passes" (b=0) to "every representable value passes" (b=5,
+        // since Fp31::PRIME = 31 < 2^5).
+        for b in 0..=5_u32 {
+            let limit = 1_u128 << b;
+            if limit > 1 {
+                assert_eq!(
+                    validate(limit - 1, b).await,
+                    Fp31::ONE,
+                    "{}, the top of the range for b={b}, should be valid",
+                    limit - 1
+                );
+            }
+            if limit < Fp31::PRIME {
+                assert_eq!(
+                    validate(limit, b).await,
+                    Fp31::ZERO,
+                    "{limit}, the first value outside the range for b={b}, should be invalid"
+                );
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
     ff::{Fp32BitPrime, GaloisField, Gf40Bit, Gf8Bit, PrimeField, Serializable},
-    hpke::{open_in_place, seal_in_place, CryptError, Info, KeyRegistry, MatchKeyCrypt},
+    hpke::{
+        open_in_place, seal_in_place, CryptError, Info, KeyRegistry, MatchKeyCrypt,
+        PrivateKeyProvider,
+    },
     secret_sharing::replicated::semi_honest::AdditiveShare as Replicated,
 };
 use bytes::BufMut;
@@ -8,8 +11,11 @@ use generic_array::GenericArray;
 use hpke::Serializable as _;
 use rand_core::{CryptoRng, RngCore};
 use std::{
+    collections::HashSet,
     fmt::{Display, Formatter},
-    marker::PhantomData, ops::Deref,
+    marker::PhantomData,
+    ops::Deref,
+    sync::Mutex,
 };
 use typenum::Unsigned;
 
@@ -74,6 +80,51 @@ impl From<&EventType> for u8 {
     }
 }
 
+/// Which of the `hpke` crate's AEAD suites a report's match-key/trigger-value ciphertexts were
+/// sealed under, carried in the report bytes alongside `key_id` so a `decrypt` can dispatch to
+/// the matching suite without the helper having to already know it out of band. [`KeyRegistry`]
+/// picks one per `key_id` (see its `aead_id` accessor), so a mixed-hardware helper fleet can run
+/// AES-128-GCM where AES-NI is available and ChaCha20-Poly1305 elsewhere, and suites can be
+/// rotated by minting a new `key_id` rather than breaking reports already in flight.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum HpkeAeadId {
+    Aes128Gcm,
+    ChaCha20Poly1305,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnsupportedSuiteError(u8);
+
+impl Display for UnsupportedSuiteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsupported HPKE AEAD suite id: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedSuiteError {}
+
+impl TryFrom<u8> for HpkeAeadId {
+    type Error = UnsupportedSuiteError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Aes128Gcm),
+            1 => Ok(Self::ChaCha20Poly1305),
+            _ => Err(UnsupportedSuiteError(value)),
+        }
+    }
+}
+
+impl From<HpkeAeadId> for u8 {
+    fn from(value: HpkeAeadId) -> Self {
+        match value {
+            HpkeAeadId::Aes128Gcm => 0,
+            HpkeAeadId::ChaCha20Poly1305 => 1,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct NonAsciiStringError {
     input: String,
@@ -116,6 +167,132 @@ pub enum InvalidReportError {
     NonAsciiString(#[from] NonAsciiStringError),
     #[error("en/decryption failure: {0}")]
     Crypt(#[from] CryptError),
+    #[error("{0}")]
+    Replay(#[from] ReplayError),
+    #[error("{0}")]
+    UnsupportedSuite(#[from] UnsupportedSuiteError),
+    #[error("expected a {expected}-byte plaintext, got {actual} bytes")]
+    BadPlaintextLength { expected: usize, actual: usize },
+    #[error("truncated or malformed report: expected at least {expected} bytes, got {actual}")]
+    TruncatedReport { expected: usize, actual: usize },
+}
+
+/// A single-use nonce identifying a report, borrowed from DAP's report-id anti-replay mechanism.
+/// Authenticated as HPKE associated data (see [`Report::encrypt_to`]) rather than merely carried
+/// in cleartext, so a collector can't pair a stolen ciphertext with a different id, and checked
+/// against a [`ReplayGuard`] on decryption so the same id can't be processed twice.
+pub type ReportId = [u8; 16];
+
+#[derive(Debug, thiserror::Error)]
+#[error("report {report_id:?} already processed in epoch {epoch}")]
+pub struct ReplayError {
+    epoch: Epoch,
+    report_id: ReportId,
+}
+
+/// Rejects a report whose `(epoch, report_id)` pair has already been processed, closing the gap
+/// where a malicious collector could resubmit identical encrypted reports to skew aggregation.
+/// `report_id` is only required to be unique within an epoch, so the pair (not `report_id` alone)
+/// is what's tracked.
+pub trait ReplayGuard {
+    /// Record `report_id` as processed for `epoch`. Fails if this pair was already recorded.
+    fn check(&self, epoch: Epoch, report_id: ReportId) -> Result<(), ReplayError>;
+}
+
+/// A [`ReplayGuard`] backed by an exact in-memory set: precise, at the cost of memory
+/// proportional to the number of reports retained. A counting Bloom filter would trade that for
+/// a bounded false-positive rate at constant memory, and can implement this same trait as a
+/// drop-in alternative once report volume makes the exact set too large to keep around.
+#[derive(Default)]
+pub struct HashSetReplayGuard {
+    seen: Mutex<HashSet<(Epoch, ReportId)>>,
+}
+
+impl ReplayGuard for HashSetReplayGuard {
+    fn check(&self, epoch: Epoch, report_id: ReportId) -> Result<(), ReplayError> {
+        if self.seen.lock().unwrap().insert((epoch, report_id)) {
+            Ok(())
+        } else {
+            Err(ReplayError { epoch, report_id })
+        }
+    }
+}
+
+/// Seal `plaintext` under whichever of the `hpke` crate's AEAD suite types `aead_id` names,
+/// returning owned bytes (rather than `seal_in_place`'s in-place-borrowing signature) so both
+/// suite branches can share one return type despite using different concrete `Aead` types.
+fn seal_suite<R: CryptoRng + RngCore>(
+    aead_id: HpkeAeadId,
+    key_registry: &KeyRegistry,
+    plaintext: &mut [u8],
+    info: Info,
+    rng: &mut R,
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), CryptError> {
+    match aead_id {
+        HpkeAeadId::Aes128Gcm => {
+            let (encap_key, ciphertext, tag) =
+                seal_in_place::<hpke::aead::AesGcm128>(key_registry, plaintext, info, rng)?;
+            Ok((
+                encap_key.to_bytes().to_vec(),
+                ciphertext.to_vec(),
+                tag.to_bytes().to_vec(),
+            ))
+        }
+        HpkeAeadId::ChaCha20Poly1305 => {
+            let (encap_key, ciphertext, tag) =
+                seal_in_place::<hpke::aead::ChaCha20Poly1305>(key_registry, plaintext, info, rng)?;
+            Ok((
+                encap_key.to_bytes().to_vec(),
+                ciphertext.to_vec(),
+                tag.to_bytes().to_vec(),
+            ))
+        }
+    }
+}
+
+/// Where a helper's HPKE decryption keys live, abstracting "key material resident in this
+/// process" ([`KeyRegistry`], used by every test and the default deployment) from "key material
+/// held by an external secure element that this process never sees in the clear" (an operator
+/// opting into that is assumed to implement this against a command-channel client that addresses
+/// the element by key slot and nonces each request, matching the request/response framing typical
+/// crypto-auth devices use). [`EncryptedReport::decrypt`] and
+/// [`EncryptedMultiHelperReport::decrypt_for`] are written against this trait rather than against
+/// `KeyRegistry` concretely so swapping backends doesn't touch report framing at all.
+pub trait PrivateKeyProvider {
+    /// Open `ciphertext` (sealed under whichever of the `hpke` crate's AEAD suites `aead_id`
+    /// names) and return the plaintext. Implementations pick the concrete `Aead` type `aead_id`
+    /// names and dispatch to it themselves (mirroring what the now-removed `open_suite` free
+    /// function used to do for [`KeyRegistry`] directly), since that choice is internal to how
+    /// each backend talks to its key material.
+    fn open(
+        &self,
+        aead_id: HpkeAeadId,
+        encap_key: &[u8],
+        ciphertext: &mut [u8],
+        info: Info,
+    ) -> Result<Vec<u8>, CryptError>;
+}
+
+impl PrivateKeyProvider for KeyRegistry {
+    /// The same suite dispatch the now-removed `open_suite` free function used to do for
+    /// `KeyRegistry` directly, just reached through the trait instead.
+    fn open(
+        &self,
+        aead_id: HpkeAeadId,
+        encap_key: &[u8],
+        ciphertext: &mut [u8],
+        info: Info,
+    ) -> Result<Vec<u8>, CryptError> {
+        match aead_id {
+            HpkeAeadId::Aes128Gcm => Ok(
+                open_in_place::<hpke::aead::AesGcm128>(self, encap_key, ciphertext, info)?.to_vec(),
+            ),
+            HpkeAeadId::ChaCha20Poly1305 => Ok(open_in_place::<hpke::aead::ChaCha20Poly1305>(
+                self, encap_key, ciphertext, info,
+            )?
+            .to_vec()),
+        }
+    }
 }
 
 /// A binary report as submitted by a report collector, containing encrypted match key shares.
@@ -137,22 +314,29 @@ where
 // Report structure:
 //  * 0..4: `timestamp`
 //  * 4: `breakdown_key`
-//  * 5..13: `trigger_value`
-//  * 13..a: `encap_key`
+//  * 5..21: `report_id`
+//  * 21..29: `trigger_value`
+//  * 29..a: `encap_key`
 //  * a..b: `mk_ciphertext`
 //  * b: `event_type`
 //  * b+1: `key_id`
-//  * b+2..b+4: `epoch`
-//  * b+4..: `site_domain`
+//  * b+2: `aead_id`
+//  * b+3..b+5: `epoch`
+//  * b+5..: `site_domain`
 impl<B: Deref<Target = [u8]>> EncryptedReport<Fp32BitPrime, Gf40Bit, Gf8Bit, B> {
     // Constants are defined for:
     //  1. Offsets that are calculated from typenum values
     //  2. Offsets that appear in the code in more places than two successive accessors. (Some
     //     offsets are used by validations in the `from_bytes` constructor.)
-    const CIPHERTEXT_OFFSET: usize = 13 + <Gf40Bit as MatchKeyCrypt>::EncapKeySize::USIZE;
+    const REPORT_ID_OFFSET: usize = 5;
+    const TRIGGER_VALUE_OFFSET: usize = Self::REPORT_ID_OFFSET + std::mem::size_of::<ReportId>();
+    const ENCAP_KEY_OFFSET: usize = Self::TRIGGER_VALUE_OFFSET + 8;
+    const CIPHERTEXT_OFFSET: usize =
+        Self::ENCAP_KEY_OFFSET + <Gf40Bit as MatchKeyCrypt>::EncapKeySize::USIZE;
     const EVENT_TYPE_OFFSET: usize =
         Self::CIPHERTEXT_OFFSET + <Gf40Bit as MatchKeyCrypt>::CiphertextSize::USIZE;
-    const SITE_DOMAIN_OFFSET: usize = Self::EVENT_TYPE_OFFSET + 4;
+    const AEAD_ID_OFFSET: usize = Self::EVENT_TYPE_OFFSET + 2;
+    const SITE_DOMAIN_OFFSET: usize = Self::AEAD_ID_OFFSET + 3;
 
     fn timestamp(&self) -> u32 {
         u32::from_le_bytes(self.data[0..4].try_into().unwrap()) // infallible slice-to-array conversion
@@ -162,12 +346,20 @@ impl<B: Deref<Target = [u8]>> EncryptedReport<Fp32BitPrime, Gf40Bit, Gf8Bit, B>
         Gf8Bit::deserialize(GenericArray::from_slice(&[self.data[4]]))
     }
 
+    fn report_id(&self) -> ReportId {
+        self.data[Self::REPORT_ID_OFFSET..Self::TRIGGER_VALUE_OFFSET]
+            .try_into()
+            .unwrap() // infallible slice-to-array conversion
+    }
+
     fn trigger_value(&self) -> Replicated<Fp32BitPrime> {
-        Replicated::<Fp32BitPrime>::deserialize(GenericArray::from_slice(&self.data[5..13]))
+        Replicated::<Fp32BitPrime>::deserialize(GenericArray::from_slice(
+            &self.data[Self::TRIGGER_VALUE_OFFSET..Self::ENCAP_KEY_OFFSET],
+        ))
     }
 
     fn encap_key(&self) -> &[u8] {
-        &self.data[13..Self::CIPHERTEXT_OFFSET]
+        &self.data[Self::ENCAP_KEY_OFFSET..Self::CIPHERTEXT_OFFSET]
     }
 
     fn match_key_ciphertext(&self) -> &[u8] {
@@ -182,9 +374,13 @@ impl<B: Deref<Target = [u8]>> EncryptedReport<Fp32BitPrime, Gf40Bit, Gf8Bit, B>
         self.data[Self::EVENT_TYPE_OFFSET + 1]
     }
 
+    fn aead_id(&self) -> HpkeAeadId {
+        HpkeAeadId::try_from(self.data[Self::AEAD_ID_OFFSET]).unwrap() // validated on construction
+    }
+
     fn epoch(&self) -> Epoch {
         u16::from_le_bytes(
-            self.data[Self::EVENT_TYPE_OFFSET + 2..Self::SITE_DOMAIN_OFFSET]
+            self.data[Self::AEAD_ID_OFFSET + 1..Self::SITE_DOMAIN_OFFSET]
                 .try_into()
                 .unwrap(), // infallible slice-to-array conversion
         )
@@ -197,6 +393,7 @@ impl<B: Deref<Target = [u8]>> EncryptedReport<Fp32BitPrime, Gf40Bit, Gf8Bit, B>
     #[allow(dead_code)] // TODO: temporary
     fn from_bytes(bytes: B) -> Result<Self, InvalidReportError> {
         EventType::try_from(bytes[Self::EVENT_TYPE_OFFSET])?;
+        HpkeAeadId::try_from(bytes[Self::AEAD_ID_OFFSET])?;
         let site_domain = &bytes[Self::SITE_DOMAIN_OFFSET..];
         if !site_domain.is_ascii() {
             return Err(NonAsciiStringError::from(site_domain).into());
@@ -210,25 +407,31 @@ impl<B: Deref<Target = [u8]>> EncryptedReport<Fp32BitPrime, Gf40Bit, Gf8Bit, B>
     #[allow(dead_code)] // TODO: temporary
     fn decrypt(
         &self,
-        key_registry: &KeyRegistry,
+        key_provider: &dyn PrivateKeyProvider,
+        replay_guard: &impl ReplayGuard,
     ) -> Result<Report<Fp32BitPrime, Gf40Bit, Gf8Bit>, InvalidReportError> {
+        replay_guard.check(self.epoch(), self.report_id())?;
+
         let info = Info::new(
             self.key_id(),
             self.epoch(),
             self.event_type(),
             HELPER_ORIGIN,
             self.site_domain(),
+            self.report_id(),
         )
         .unwrap(); // validated on construction
 
         let mut ciphertext: GenericArray<u8, <Gf40Bit as MatchKeyCrypt>::CiphertextSize> =
             GenericArray::clone_from_slice(self.match_key_ciphertext());
-        let plaintext = open_in_place(key_registry, self.encap_key(), &mut ciphertext, info)?;
+        let plaintext =
+            key_provider.open(self.aead_id(), self.encap_key(), ciphertext.as_mut(), info)?;
 
         Ok(Report {
             timestamp: self.timestamp(),
+            report_id: self.report_id(),
             mk_shares: <Gf40Bit as MatchKeyCrypt>::SemiHonestShares::deserialize(
-                GenericArray::from_slice(plaintext),
+                GenericArray::from_slice(&plaintext),
             ),
             event_type: self.event_type(),
             breakdown_key: self.breakdown_key(),
@@ -248,6 +451,7 @@ where
     BK: GaloisField,
 {
     pub timestamp: u32,
+    pub report_id: ReportId,
     pub mk_shares: <MK as MatchKeyCrypt>::SemiHonestShares,
     pub event_type: EventType,
     pub breakdown_key: BK,
@@ -282,19 +486,21 @@ where
         rng: &mut R,
         out: &mut B,
     ) -> Result<(), InvalidReportError> {
+        let aead_id = key_registry.aead_id(key_id);
         let info = Info::new(
             key_id,
             self.epoch,
             self.event_type,
             HELPER_ORIGIN,
             self.site_domain.as_ref(),
+            self.report_id,
         )?;
 
         let mut plaintext = GenericArray::default();
         self.mk_shares.serialize(&mut plaintext);
 
         let (encap_key, ciphertext, tag) =
-            seal_in_place(key_registry, plaintext.as_mut(), info, rng)?;
+            seal_suite(aead_id, key_registry, plaintext.as_mut(), info, rng)?;
 
         out.put_slice(&self.timestamp.to_le_bytes());
 
@@ -302,14 +508,17 @@ where
         self.breakdown_key.serialize(&mut bk);
         out.put_slice(bk.as_slice());
 
+        out.put_slice(&self.report_id);
+
         let mut trigger_value = GenericArray::default();
         self.trigger_value.serialize(&mut trigger_value);
         out.put_slice(trigger_value.as_slice());
-        out.put_slice(&encap_key.to_bytes());
-        out.put_slice(ciphertext);
-        out.put_slice(&tag.to_bytes());
+        out.put_slice(&encap_key);
+        out.put_slice(&ciphertext);
+        out.put_slice(&tag);
         out.put_slice(&[u8::from(&self.event_type)]);
         out.put_slice(&[key_id]);
+        out.put_slice(&[u8::from(aead_id)]);
         out.put_slice(&self.epoch.to_le_bytes());
         out.put_slice(self.site_domain.as_bytes());
 
@@ -317,6 +526,371 @@ where
     }
 }
 
+/// Number of MPC helpers a [`Report`] fans out to. Fixed at three for the honest-majority
+/// protocol this crate implements.
+pub const HELPER_COUNT: usize = 3;
+
+/// A helper's HPKE public key, as accepted by [`Report::encrypt_to_helpers`]. In this crate a
+/// helper's key material is exposed through its [`KeyRegistry`] (the same handle
+/// [`Report::encrypt`]/[`EncryptedReport::decrypt`] already thread through for the
+/// single-recipient case), so this is just a borrow of one rather than a new key type.
+pub type HelperPublicKey<'a> = &'a KeyRegistry;
+
+/// Distinguishes the `Info`/AAD binding used for the `helper_index`'th slot of an
+/// [`EncryptedMultiHelperReport`] from every other helper's slot, so a ciphertext sealed for one
+/// helper can't be opened as if it were sealed for another, even though all three slots otherwise
+/// share `key_id`/`epoch`/`event_type`/`site_domain`.
+fn helper_origin(helper_index: usize) -> String {
+    format!("{HELPER_ORIGIN}/helper-{helper_index}")
+}
+
+/// Tags passed to [`slot_origin`] for the two ciphertexts sealed per helper.
+const MK_SLOT: &str = "mk";
+const TRIGGER_VALUE_SLOT: &str = "trigger-value";
+
+/// Further distinguishes the match-key slot's `Info`/AAD binding from the trigger-value slot's,
+/// on top of [`helper_origin`]'s per-helper distinction, so that within one helper's entry the
+/// match-key ciphertext can't be swapped in for the trigger-value ciphertext (or vice versa) and
+/// still decrypt.
+fn slot_origin(helper_index: usize, slot: &str) -> String {
+    format!("{}/{slot}", helper_origin(helper_index))
+}
+
+/// Append `len: u16` followed by `bytes` to `out`. Unlike [`EncryptedReport`]'s fixed byte
+/// offsets (sound there because `Gf40Bit`/`Fp32BitPrime` have one fixed HPKE ciphersuite's worth
+/// of sizes to work with), [`EncryptedMultiHelperReport`] seals two independently-sized fields
+/// (match key share, trigger value share) per helper, so its slots are length-prefixed instead.
+fn put_length_prefixed(out: &mut impl BufMut, bytes: &[u8]) {
+    out.put_slice(&u16::try_from(bytes.len()).unwrap().to_le_bytes());
+    out.put_slice(bytes);
+}
+
+/// The inverse of [`put_length_prefixed`]: read one length-prefixed field starting at `*offset`,
+/// advancing `*offset` past it. `data` is the raw report envelope, which a collector (or an
+/// attacker who tampered with, but re-sealed, a report) fully controls, so this bounds-checks the
+/// length prefix and the field itself against `data.len()` rather than indexing blindly.
+fn get_length_prefixed<'a>(
+    data: &'a [u8],
+    offset: &mut usize,
+) -> Result<&'a [u8], InvalidReportError> {
+    let header_end = *offset + 2;
+    let Some(len_bytes) = data.get(*offset..header_end) else {
+        return Err(InvalidReportError::TruncatedReport {
+            expected: header_end,
+            actual: data.len(),
+        });
+    };
+    let len = u16::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+    let field_end = header_end + len;
+    let Some(field) = data.get(header_end..field_end) else {
+        return Err(InvalidReportError::TruncatedReport {
+            expected: field_end,
+            actual: data.len(),
+        });
+    };
+    *offset = field_end;
+    Ok(field)
+}
+
+/// `GenericArray::from_slice` panics if `bytes.len() != N`. Since `bytes` here is HPKE plaintext
+/// recovered from a length-prefixed field whose length a collector (or an attacker who tampered
+/// with, but re-sealed, a report) fully controls, check the length first and report a malformed
+/// report instead of crashing the helper that's decrypting it.
+fn checked_generic_array<N: generic_array::ArrayLength<u8>>(
+    bytes: &[u8],
+) -> Result<&GenericArray<u8, N>, InvalidReportError> {
+    if bytes.len() == N::USIZE {
+        Ok(GenericArray::from_slice(bytes))
+    } else {
+        Err(InvalidReportError::BadPlaintextLength {
+            expected: N::USIZE,
+            actual: bytes.len(),
+        })
+    }
+}
+
+/// A binary report following the DAP report structure: one shared cleartext header (`timestamp`,
+/// `breakdown_key`, `event_type`, `key_id`, `epoch`, `site_domain`) plus one HPKE-sealed input
+/// share per MPC helper, so a report collector emits a single canonical wire object instead of
+/// [`HELPER_COUNT`] loosely-coupled [`EncryptedReport`] blobs. See [`Report::encrypt_to_helpers`]
+/// and [`Self::decrypt_for`].
+///
+/// Layout: `timestamp` (4 bytes) | `breakdown_key` | `report_id` (16 bytes) | then, for each of
+/// [`HELPER_COUNT`] helpers in order: `aead_id` (1 byte), length-prefixed match-key encap key,
+/// length-prefixed match-key ciphertext (with appended AEAD tag), length-prefixed trigger-value
+/// encap key, length-prefixed trigger-value ciphertext (with appended tag) | `event_type`
+/// (1 byte) | `key_id` (1 byte) | `epoch` (2 bytes) | `site_domain` (remaining bytes).
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct EncryptedMultiHelperReport<F, MK, BK, B>
+where
+    B: Deref<Target = [u8]>,
+    F: PrimeField,
+    Replicated<F>: Serializable,
+    MK: MatchKeyCrypt,
+    BK: GaloisField,
+{
+    data: B,
+    phantom_data: PhantomData<(F, MK, BK)>,
+}
+
+impl<B: Deref<Target = [u8]>> EncryptedMultiHelperReport<Fp32BitPrime, Gf40Bit, Gf8Bit, B> {
+    const BREAKDOWN_KEY_OFFSET: usize = 4;
+    const REPORT_ID_OFFSET: usize = Self::BREAKDOWN_KEY_OFFSET + 1;
+    const SLOTS_OFFSET: usize = Self::REPORT_ID_OFFSET + std::mem::size_of::<ReportId>();
+
+    fn timestamp(&self) -> u32 {
+        u32::from_le_bytes(self.data[0..4].try_into().unwrap())
+    }
+
+    fn breakdown_key(&self) -> Gf8Bit {
+        Gf8Bit::deserialize(GenericArray::from_slice(
+            &self.data[Self::BREAKDOWN_KEY_OFFSET..Self::REPORT_ID_OFFSET],
+        ))
+    }
+
+    fn report_id(&self) -> ReportId {
+        self.data[Self::REPORT_ID_OFFSET..Self::SLOTS_OFFSET]
+            .try_into()
+            .unwrap() // infallible slice-to-array conversion
+    }
+
+    /// Scan past every helper slot up to (but not including) `helper_index`, returning its byte
+    /// offset. Slots are variable-length (HPKE ciphertexts), so this is a linear scan rather than
+    /// a typenum-computed constant, same as [`Self::tail_offset`]. Fallible, since `self.data` is
+    /// raw, attacker-reachable bytes that may be truncated partway through a slot.
+    fn slot_offset(&self, helper_index: usize) -> Result<usize, InvalidReportError> {
+        let mut offset = Self::SLOTS_OFFSET;
+        for _ in 0..helper_index {
+            if self.data.get(offset).is_none() {
+                return Err(InvalidReportError::TruncatedReport {
+                    expected: offset + 1,
+                    actual: self.data.len(),
+                });
+            }
+            offset += 1; // aead_id
+            get_length_prefixed(&self.data, &mut offset)?; // mk encap key
+            get_length_prefixed(&self.data, &mut offset)?; // mk ciphertext
+            get_length_prefixed(&self.data, &mut offset)?; // trigger encap key
+            get_length_prefixed(&self.data, &mut offset)?; // trigger ciphertext
+        }
+        Ok(offset)
+    }
+
+    /// The AEAD suite `helper_index`'s slot was sealed under. Unlike [`EncryptedReport`] (one
+    /// recipient, one suite), each slot here may have been sealed to a different helper's
+    /// `KeyRegistry`, which may itself prefer a different suite, so this is carried per-slot
+    /// rather than once in the shared header.
+    fn slot_aead_id(&self, helper_index: usize) -> HpkeAeadId {
+        let offset = self.slot_offset(helper_index).expect("validated on construction");
+        HpkeAeadId::try_from(self.data[offset]).unwrap() // validated on construction
+    }
+
+    /// Byte offset of the shared cleartext tail (`event_type`/`key_id`/`epoch`/`site_domain`),
+    /// just past the last helper's slot.
+    fn tail_offset(&self) -> Result<usize, InvalidReportError> {
+        self.slot_offset(HELPER_COUNT)
+    }
+
+    fn event_type(&self) -> EventType {
+        let tail = self.tail_offset().expect("validated on construction");
+        EventType::try_from(self.data[tail]).unwrap() // validated on construction
+    }
+
+    fn key_id(&self) -> KeyIdentifier {
+        self.data[self.tail_offset().expect("validated on construction") + 1]
+    }
+
+    fn epoch(&self) -> Epoch {
+        let tail = self.tail_offset().expect("validated on construction");
+        u16::from_le_bytes(self.data[tail + 2..tail + 4].try_into().unwrap())
+    }
+
+    fn site_domain(&self) -> &str {
+        let tail = self.tail_offset().expect("validated on construction");
+        std::str::from_utf8(&self.data[tail + 4..]).unwrap() // validated on construction
+    }
+
+    pub fn from_bytes(bytes: B) -> Result<Self, InvalidReportError> {
+        if bytes.len() < Self::SLOTS_OFFSET {
+            return Err(InvalidReportError::TruncatedReport {
+                expected: Self::SLOTS_OFFSET,
+                actual: bytes.len(),
+            });
+        }
+        let this = Self {
+            data: bytes,
+            phantom_data: PhantomData,
+        };
+        // Scanning all the way to the tail fully parses (and bounds-checks) every helper's slot,
+        // including the last one's, which the per-slot loop below never reaches on its own.
+        let tail = this.tail_offset()?;
+        for helper_index in 0..HELPER_COUNT {
+            HpkeAeadId::try_from(this.data[this.slot_offset(helper_index)?])?;
+        }
+        if this.data.len() < tail + 4 {
+            return Err(InvalidReportError::TruncatedReport {
+                expected: tail + 4,
+                actual: this.data.len(),
+            });
+        }
+        EventType::try_from(this.data[tail])?;
+        let site_domain = &this.data[tail + 4..];
+        if !site_domain.is_ascii() {
+            return Err(NonAsciiStringError::from(site_domain).into());
+        }
+        Ok(this)
+    }
+
+    /// Open only `helper_index`'s slot, skipping the ciphertexts sealed to the other
+    /// [`HELPER_COUNT`] `- 1` helpers, recovering this helper's view of the [`Report`].
+    pub fn decrypt_for(
+        &self,
+        helper_index: usize,
+        key_provider: &dyn PrivateKeyProvider,
+        replay_guard: &impl ReplayGuard,
+    ) -> Result<Report<Fp32BitPrime, Gf40Bit, Gf8Bit>, InvalidReportError> {
+        replay_guard.check(self.epoch(), self.report_id())?;
+
+        let mk_info = Info::new(
+            self.key_id(),
+            self.epoch(),
+            self.event_type(),
+            &slot_origin(helper_index, MK_SLOT),
+            self.site_domain(),
+            self.report_id(),
+        )
+        .unwrap(); // validated on construction
+
+        let aead_id = self.slot_aead_id(helper_index);
+        // Already scanned without truncation in `from_bytes`, so these can't fail here.
+        let mut offset = self.slot_offset(helper_index).expect("validated on construction") + 1; // skip the slot's aead_id byte
+        let mk_encap_key = get_length_prefixed(&self.data, &mut offset)
+            .expect("validated on construction")
+            .to_vec();
+        let mut mk_ciphertext = get_length_prefixed(&self.data, &mut offset)
+            .expect("validated on construction")
+            .to_vec();
+        let trigger_encap_key = get_length_prefixed(&self.data, &mut offset)
+            .expect("validated on construction")
+            .to_vec();
+        let mut trigger_ciphertext = get_length_prefixed(&self.data, &mut offset)
+            .expect("validated on construction")
+            .to_vec();
+
+        let mk_plaintext =
+            key_provider.open(aead_id, &mk_encap_key, &mut mk_ciphertext, mk_info)?;
+        let mk_shares = <Gf40Bit as MatchKeyCrypt>::SemiHonestShares::deserialize(
+            checked_generic_array(&mk_plaintext)?,
+        );
+
+        let trigger_info = Info::new(
+            self.key_id(),
+            self.epoch(),
+            self.event_type(),
+            &slot_origin(helper_index, TRIGGER_VALUE_SLOT),
+            self.site_domain(),
+            self.report_id(),
+        )
+        .unwrap();
+        let trigger_plaintext = key_provider.open(
+            aead_id,
+            &trigger_encap_key,
+            &mut trigger_ciphertext,
+            trigger_info,
+        )?;
+        let trigger_value =
+            Replicated::<Fp32BitPrime>::deserialize(checked_generic_array(&trigger_plaintext)?);
+
+        Ok(Report {
+            timestamp: self.timestamp(),
+            report_id: self.report_id(),
+            mk_shares,
+            event_type: self.event_type(),
+            breakdown_key: self.breakdown_key(),
+            trigger_value,
+            epoch: self.epoch(),
+            site_domain: self.site_domain().to_owned(),
+        })
+    }
+}
+
+impl Report<Fp32BitPrime, Gf40Bit, Gf8Bit> {
+    /// Seal one report per MPC helper into a single [`EncryptedMultiHelperReport`]:
+    /// `reports[i]` is this collector's view of the share helper `i` should receive, sealed to
+    /// `helper_keys[i]`. `reports` must agree on `timestamp`/`report_id`/`breakdown_key`/
+    /// `event_type`/`epoch`/`site_domain` (only `reports[0]`'s copies of those are written to the
+    /// shared header); they differ in `mk_shares`/`trigger_value`, each helper's own replicated
+    /// share.
+    /// Lets a collector emit one canonical wire object instead of [`HELPER_COUNT`]
+    /// loosely-coupled [`EncryptedReport`] blobs, following DAP's one-encrypted-input-share-per-
+    /// aggregator report structure.
+    pub fn encrypt_to_helpers<R: CryptoRng + RngCore>(
+        reports: &[Self; HELPER_COUNT],
+        key_id: KeyIdentifier,
+        helper_keys: &[HelperPublicKey<'_>; HELPER_COUNT],
+        rng: &mut R,
+    ) -> Result<Vec<u8>, InvalidReportError> {
+        let header = &reports[0];
+        let mut out = Vec::new();
+        out.put_slice(&header.timestamp.to_le_bytes());
+        let mut bk = GenericArray::default();
+        header.breakdown_key.serialize(&mut bk);
+        out.put_slice(bk.as_slice());
+        out.put_slice(&header.report_id);
+
+        for (i, report) in reports.iter().enumerate() {
+            let aead_id = helper_keys[i].aead_id(key_id);
+            out.put_slice(&[u8::from(aead_id)]);
+
+            let mk_info = Info::new(
+                key_id,
+                header.epoch,
+                header.event_type,
+                &slot_origin(i, MK_SLOT),
+                header.site_domain.as_ref(),
+                header.report_id,
+            )?;
+            let mut mk_plaintext = GenericArray::default();
+            report.mk_shares.serialize(&mut mk_plaintext);
+            let (mk_encap_key, mk_ciphertext, mk_tag) =
+                seal_suite(aead_id, helper_keys[i], mk_plaintext.as_mut(), mk_info, rng)?;
+            put_length_prefixed(&mut out, &mk_encap_key);
+            let mut mk_sealed = mk_ciphertext;
+            mk_sealed.extend_from_slice(&mk_tag);
+            put_length_prefixed(&mut out, &mk_sealed);
+
+            let trigger_info = Info::new(
+                key_id,
+                header.epoch,
+                header.event_type,
+                &slot_origin(i, TRIGGER_VALUE_SLOT),
+                header.site_domain.as_ref(),
+                header.report_id,
+            )?;
+            let mut trigger_plaintext = GenericArray::default();
+            report.trigger_value.serialize(&mut trigger_plaintext);
+            let (trigger_encap_key, trigger_ciphertext, trigger_tag) = seal_suite(
+                aead_id,
+                helper_keys[i],
+                trigger_plaintext.as_mut(),
+                trigger_info,
+                rng,
+            )?;
+            put_length_prefixed(&mut out, &trigger_encap_key);
+            let mut trigger_sealed = trigger_ciphertext;
+            trigger_sealed.extend_from_slice(&trigger_tag);
+            put_length_prefixed(&mut out, &trigger_sealed);
+        }
+
+        out.put_slice(&[u8::from(&header.event_type)]);
+        out.put_slice(&[key_id]);
+        out.put_slice(&header.epoch.to_le_bytes());
+        out.put_slice(header.site_domain.as_bytes());
+
+        Ok(out)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::ff::{Fp32BitPrime, Gf40Bit, Gf8Bit};
@@ -332,6 +906,7 @@ mod test {
 
         let report = Report::<Fp32BitPrime, Gf40Bit, Gf8Bit> {
             timestamp: rng.gen(),
+            report_id: rng.gen(),
             mk_shares: (rng.gen(), rng.gen()).into(),
             event_type: EventType::Trigger,
             breakdown_key: rng.gen(),
@@ -346,20 +921,190 @@ mod test {
 
         let key_registry = KeyRegistry::random(1, &mut rng);
         let key_id = 0;
+        let replay_guard = HashSetReplayGuard::default();
 
         let enc_report_bytes = report.encrypt(key_id, &key_registry, &mut rng).unwrap();
         let enc_report = EncryptedReport::from_bytes(enc_report_bytes.as_slice()).unwrap();
-        let dec_report = enc_report.decrypt(&key_registry).unwrap();
+        let dec_report = enc_report.decrypt(&key_registry, &replay_guard).unwrap();
 
         assert_eq!(dec_report, report);
     }
 
+    #[test]
+    fn rejects_replayed_report() {
+        let mut rng = StdRng::from_seed([1_u8; 32]);
+
+        let report = Report::<Fp32BitPrime, Gf40Bit, Gf8Bit> {
+            timestamp: rng.gen(),
+            report_id: rng.gen(),
+            mk_shares: (rng.gen(), rng.gen()).into(),
+            event_type: EventType::Trigger,
+            breakdown_key: rng.gen(),
+            trigger_value: (rng.gen(), rng.gen()).into(),
+            epoch: rng.gen(),
+            site_domain: (&mut rng)
+                .sample_iter(Alphanumeric)
+                .map(char::from)
+                .take(10)
+                .collect(),
+        };
+
+        let key_registry = KeyRegistry::random(1, &mut rng);
+        let key_id = 0;
+        let replay_guard = HashSetReplayGuard::default();
+
+        let enc_report_bytes = report.encrypt(key_id, &key_registry, &mut rng).unwrap();
+        let enc_report = EncryptedReport::from_bytes(enc_report_bytes.as_slice()).unwrap();
+
+        enc_report.decrypt(&key_registry, &replay_guard).unwrap();
+        let err = enc_report
+            .decrypt(&key_registry, &replay_guard)
+            .err()
+            .unwrap();
+        assert!(matches!(err, InvalidReportError::Replay(_)));
+    }
+
+    #[test]
+    fn multi_helper_enc_dec_roundtrip() {
+        let mut rng = StdRng::from_seed([1_u8; 32]);
+
+        let site_domain: String = (&mut rng)
+            .sample_iter(Alphanumeric)
+            .map(char::from)
+            .take(10)
+            .collect();
+        let timestamp = rng.gen();
+        let event_type = EventType::Trigger;
+        let breakdown_key = rng.gen();
+        let epoch = rng.gen();
+
+        let report_id = rng.gen();
+        let reports: [Report<Fp32BitPrime, Gf40Bit, Gf8Bit>; HELPER_COUNT] =
+            std::array::from_fn(|_| Report {
+                timestamp,
+                report_id,
+                mk_shares: (rng.gen(), rng.gen()).into(),
+                event_type,
+                breakdown_key,
+                trigger_value: (rng.gen(), rng.gen()).into(),
+                epoch,
+                site_domain: site_domain.clone(),
+            });
+
+        let key_registries: [KeyRegistry; HELPER_COUNT] =
+            std::array::from_fn(|_| KeyRegistry::random(1, &mut rng));
+        let key_id = 0;
+
+        let enc_report_bytes = Report::encrypt_to_helpers(
+            &reports,
+            key_id,
+            &std::array::from_fn(|i| &key_registries[i]),
+            &mut rng,
+        )
+        .unwrap();
+        let enc_report =
+            EncryptedMultiHelperReport::from_bytes(enc_report_bytes.as_slice()).unwrap();
+
+        // Each helper keeps its own replay state in practice, so each gets its own guard here.
+        for (i, key_registry) in key_registries.iter().enumerate() {
+            let replay_guard = HashSetReplayGuard::default();
+            let dec_report = enc_report
+                .decrypt_for(i, key_registry, &replay_guard)
+                .unwrap();
+            assert_eq!(dec_report, reports[i]);
+        }
+    }
+
+    /// Re-seal helper 0's match-key slot with a plaintext that's far shorter than a real
+    /// `SemiHonestShares` encoding, using the same key/AEAD/AAD so it still opens successfully.
+    /// This is what a buggy sender (or a bit-flipped-then-re-tagged ciphertext) would produce,
+    /// and `decrypt_for` must surface it as an `InvalidReportError`, not panic on the
+    /// length-mismatched `GenericArray::from_slice` it does internally.
+    #[test]
+    fn rejects_malformed_plaintext_length_instead_of_panicking() {
+        let mut rng = StdRng::from_seed([2_u8; 32]);
+
+        let site_domain: String = (&mut rng)
+            .sample_iter(Alphanumeric)
+            .map(char::from)
+            .take(10)
+            .collect();
+        let reports: [Report<Fp32BitPrime, Gf40Bit, Gf8Bit>; HELPER_COUNT] =
+            std::array::from_fn(|_| Report {
+                timestamp: rng.gen(),
+                report_id: rng.gen(),
+                mk_shares: (rng.gen(), rng.gen()).into(),
+                event_type: EventType::Trigger,
+                breakdown_key: rng.gen(),
+                trigger_value: (rng.gen(), rng.gen()).into(),
+                epoch: rng.gen(),
+                site_domain: site_domain.clone(),
+            });
+        let key_registries: [KeyRegistry; HELPER_COUNT] =
+            std::array::from_fn(|_| KeyRegistry::random(1, &mut rng));
+        let key_id = 0;
+
+        let enc_report_bytes = Report::encrypt_to_helpers(
+            &reports,
+            key_id,
+            &std::array::from_fn(|i| &key_registries[i]),
+            &mut rng,
+        )
+        .unwrap();
+        let enc_report =
+            EncryptedMultiHelperReport::from_bytes(enc_report_bytes.as_slice()).unwrap();
+
+        let aead_id = enc_report.slot_aead_id(0);
+        let mk_info = Info::new(
+            enc_report.key_id(),
+            enc_report.epoch(),
+            enc_report.event_type(),
+            &slot_origin(0, MK_SLOT),
+            enc_report.site_domain(),
+            enc_report.report_id(),
+        )
+        .unwrap();
+
+        // Locate helper 0's match-key encap-key/ciphertext fields, which together span from
+        // just past its `aead_id` byte up to (but not including) the trigger-value fields.
+        let mut offset = enc_report.slot_offset(0).unwrap() + 1;
+        let mk_fields_start = offset;
+        get_length_prefixed(&enc_report_bytes, &mut offset).unwrap(); // mk encap key
+        get_length_prefixed(&enc_report_bytes, &mut offset).unwrap(); // mk ciphertext
+        let mk_fields_end = offset;
+
+        let mut short_plaintext = vec![0_u8; 1]; // far shorter than any real share encoding
+        let (new_encap_key, new_ciphertext, new_tag) = seal_suite(
+            aead_id,
+            &key_registries[0],
+            &mut short_plaintext,
+            mk_info,
+            &mut rng,
+        )
+        .unwrap();
+        let mut new_mk_sealed = new_ciphertext;
+        new_mk_sealed.extend_from_slice(&new_tag);
+
+        let mut corrupted_bytes = enc_report_bytes[..mk_fields_start].to_vec();
+        put_length_prefixed(&mut corrupted_bytes, &new_encap_key);
+        put_length_prefixed(&mut corrupted_bytes, &new_mk_sealed);
+        corrupted_bytes.extend_from_slice(&enc_report_bytes[mk_fields_end..]);
+
+        let corrupted = EncryptedMultiHelperReport::from_bytes(corrupted_bytes.as_slice()).unwrap();
+        let replay_guard = HashSetReplayGuard::default();
+        let err = corrupted
+            .decrypt_for(0, &key_registries[0], &replay_guard)
+            .unwrap_err();
+        assert!(matches!(err, InvalidReportError::BadPlaintextLength { .. }));
+    }
+
     #[test]
     fn decrypt() {
         let mut rng = StdRng::from_seed([1_u8; 32]);
 
         let expected = Report::<Fp32BitPrime, Gf40Bit, Gf8Bit> {
             timestamp: rng.gen(),
+            report_id: [0_u8; 16],
             mk_shares: (rng.gen(), rng.gen()).into(),
             event_type: EventType::Trigger,
             breakdown_key: rng.gen(),
@@ -373,18 +1118,24 @@ mod test {
         };
 
         let key_registry = KeyRegistry::random(1, &mut rng);
+        let replay_guard = HashSetReplayGuard::default();
 
+        // Fixture predates the `report_id` and `aead_id` fields; a zeroed id and an
+        // `Aes128Gcm` suite byte are spliced in at their offsets rather than regenerated from
+        // real key material (this file has no working `hpke` module in this checkout to
+        // regenerate a real one against).
         let enc_report_bytes = hex::decode(
             "\
-            3301e8d7528e08671418d2164dc80a3403e4aadd01be4263b723ba2204638c20\
-            830500710b2bdb931f5f429f234abddf09109ecb2f730b368b7fa4fda0acf3db\
-            52c5d509681e8a0100783b6c64466e5531386d6c44\
+            3301e8d75200000000000000000000000000000000\
+            8e08671418d2164dc80a3403e4aadd01be4263b723ba2204638c20830500710\
+            b2bdb931f5f429f234abddf09109ecb2f730b368b7fa4fda0acf3db52c5d509\
+            681e8a010000783b6c64466e5531386d6c44\
         ",
         )
         .unwrap();
 
         let enc_report = EncryptedReport::from_bytes(enc_report_bytes.as_slice()).unwrap();
-        let report = enc_report.decrypt(&key_registry).unwrap();
+        let report = enc_report.decrypt(&key_registry, &replay_guard).unwrap();
 
         assert_eq!(report, expected);
     }
@@ -393,9 +1144,10 @@ mod test {
     fn invalid_event_type() {
         let bytes = hex::decode(
             "\
-            3301e8d7528e08671418d2164dc80a3403e4aadd01be4263b723ba2204638c20\
-            830500710b2bdb931f5f429f234abddf09109ecb2f730b368b7fa4fda0acf3db\
-            52c5d509681e8abd00783b6c64466e5531386d6c44\
+            3301e8d75200000000000000000000000000000000\
+            8e08671418d2164dc80a3403e4aadd01be4263b723ba2204638c20830500710\
+            b2bdb931f5f429f234abddf09109ecb2f730b368b7fa4fda0acf3db52c5d509\
+            681e8abd0000783b6c64466e5531386d6c44\
         ",
         )
         .unwrap();
@@ -410,9 +1162,10 @@ mod test {
     fn invalid_site_domain() {
         let bytes = hex::decode(
             "\
-            3301e8d7528e08671418d2164dc80a3403e4aadd01be4263b723ba2204638c20\
-            830500710b2bdb931f5f429f234abddf09109ecb2f730b368b7fa4fda0acf3db\
-            52c5d509681e8a0100783bff64466e5531386d6c44\
+            3301e8d75200000000000000000000000000000000\
+            8e08671418d2164dc80a3403e4aadd01be4263b723ba2204638c20830500710\
+            b2bdb931f5f429f234abddf09109ecb2f730b368b7fa4fda0acf3db52c5d509\
+            681e8a010000783bff64466e5531386d6c44\
         ",
         )
         .unwrap();
@@ -422,4 +1175,63 @@ mod test {
             .unwrap();
         assert!(matches!(err, InvalidReportError::NonAsciiString(_)));
     }
+
+    /// A truncated [`EncryptedMultiHelperReport`] envelope -- whether cut short by a collector
+    /// bug or by an attacker -- must be rejected by [`EncryptedMultiHelperReport::from_bytes`]
+    /// as an [`InvalidReportError`], not panic by indexing or slicing past the end of the buffer.
+    #[test]
+    fn rejects_truncated_envelope_instead_of_panicking() {
+        let mut rng = StdRng::from_seed([1_u8; 32]);
+
+        let site_domain: String = (&mut rng)
+            .sample_iter(Alphanumeric)
+            .map(char::from)
+            .take(10)
+            .collect();
+        let reports: [Report<Fp32BitPrime, Gf40Bit, Gf8Bit>; HELPER_COUNT] =
+            std::array::from_fn(|_| Report {
+                timestamp: rng.gen(),
+                report_id: rng.gen(),
+                mk_shares: (rng.gen(), rng.gen()).into(),
+                event_type: EventType::Trigger,
+                breakdown_key: rng.gen(),
+                trigger_value: (rng.gen(), rng.gen()).into(),
+                epoch: rng.gen(),
+                site_domain: site_domain.clone(),
+            });
+        let key_registries: [KeyRegistry; HELPER_COUNT] =
+            std::array::from_fn(|_| KeyRegistry::random(1, &mut rng));
+
+        let enc_report_bytes = Report::encrypt_to_helpers(
+            &reports,
+            0,
+            &std::array::from_fn(|i| &key_registries[i]),
+            &mut rng,
+        )
+        .unwrap();
+
+        // An empty buffer can't even hold the fixed `timestamp`/`breakdown_key`/`report_id`
+        // header.
+        let err = EncryptedMultiHelperReport::<Fp32BitPrime, Gf40Bit, Gf8Bit, _>::from_bytes(
+            &[][..],
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, InvalidReportError::TruncatedReport { .. }));
+
+        // Cutting off partway through a length-prefixed field (here, short of the first helper's
+        // slot entirely) must not panic either.
+        for cut in [
+            EncryptedMultiHelperReport::<Fp32BitPrime, Gf40Bit, Gf8Bit, &[u8]>::SLOTS_OFFSET,
+            enc_report_bytes.len() - 1,
+        ] {
+            let err =
+                EncryptedMultiHelperReport::<Fp32BitPrime, Gf40Bit, Gf8Bit, _>::from_bytes(
+                    &enc_report_bytes[..cut],
+                )
+                .err()
+                .unwrap();
+            assert!(matches!(err, InvalidReportError::TruncatedReport { .. }));
+        }
+    }
 }